@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_awesome_puzzle_game_example::game::{
+    Block, BlockGridSize, BlockIndex, BlockIndexOffset, Game, PieceSet, Stage,
+};
+
+fn stage_with_stack() -> Stage {
+    let mut stage = Stage::new(BlockGridSize::new(10, 20));
+    for y in 0..12 {
+        for x in 0..10 {
+            if (x + y * 3) % 7 != 0 || y % 4 == 3 {
+                stage.put_block(BlockIndex::new(x, y), Block::new((x % 7) as u32));
+            }
+        }
+    }
+    stage
+}
+
+fn can_put_to_by_grid(stage: &Stage, index: BlockIndexOffset) -> bool {
+    index.x >= 0
+        && (index.x as usize) < stage.size().width
+        && index.y >= 0
+        && (index.y as usize >= stage.size().height || stage.block(index.cast::<usize>()).is_none())
+}
+
+fn filled_line_indices_by_grid(stage: &Stage) -> Vec<usize> {
+    (0..stage.size().height)
+        .filter(|&y| (0..stage.size().width).all(|x| stage.block(BlockIndex::new(x, y)).is_some()))
+        .collect()
+}
+
+fn indices() -> Vec<BlockIndexOffset> {
+    (-1..22)
+        .flat_map(|y| (-1..11).map(move |x| BlockIndexOffset::new(x, y)))
+        .collect()
+}
+
+fn can_put_to(c: &mut Criterion) {
+    let stage = stage_with_stack();
+    let indices = indices();
+    let mut group = c.benchmark_group("can_put_to");
+    group.bench_function("grid", |b| {
+        b.iter(|| {
+            indices
+                .iter()
+                .filter(|&&index| can_put_to_by_grid(black_box(&stage), index))
+                .count()
+        })
+    });
+    group.bench_function("row_mask", |b| {
+        b.iter(|| {
+            indices
+                .iter()
+                .filter(|&&index| black_box(&stage).can_put_to(index))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn filled_line_indices(c: &mut Criterion) {
+    let stage = stage_with_stack();
+    let mut group = c.benchmark_group("filled_line_indices");
+    group.bench_function("grid", |b| {
+        b.iter(|| filled_line_indices_by_grid(black_box(&stage)))
+    });
+    group.bench_function("row_mask", |b| {
+        b.iter(|| black_box(&stage).filled_line_indices())
+    });
+    group.finish();
+}
+
+fn reachable_placements(c: &mut Criterion) {
+    let game = Game::with_seed(PieceSet::standard(), 0);
+    c.bench_function("reachable_placements", |b| {
+        b.iter(|| black_box(&game).reachable_placements())
+    });
+}
+
+criterion_group!(
+    benches,
+    can_put_to,
+    filled_line_indices,
+    reachable_placements
+);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
-use crate::game::block::Block;
+use crate::game::block::{Block, BlockNumber};
+use crate::pixel_font;
 use std::collections;
 use std::path;
 use std::rc;
@@ -21,6 +22,48 @@ impl BlockFace {
     }
 }
 
+const TINT_BASE_BLOCK_NUMBER: BlockNumber = 0;
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let h = (hue.fract() * 6.0).floor();
+    let f = hue.fract() * 6.0 - h;
+    let p = value * (1.0 - saturation);
+    let q = value * (1.0 - saturation * f);
+    let t = value * (1.0 - saturation * (1.0 - f));
+    match h as u32 {
+        0 => [value, t, p],
+        1 => [q, value, p],
+        2 => [p, value, t],
+        3 => [p, q, value],
+        4 => [t, p, value],
+        _ => [value, p, q],
+    }
+}
+
+fn block_palette_color(number: BlockNumber) -> [f32; 3] {
+    const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+    let saturation = [0.65, 0.45, 0.8][(number % 3) as usize];
+    hsv_to_rgb(number as f32 * GOLDEN_RATIO_CONJUGATE, saturation, 0.95)
+}
+
+fn luminance(pixel: &image::Rgba<u8>) -> f32 {
+    (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) / 255.0
+}
+
+fn tint(image: &mut image::RgbaImage, color: [f32; 3]) {
+    let max_luminance = image
+        .pixels()
+        .filter(|pixel| pixel[3] > 0)
+        .map(luminance)
+        .fold(f32::EPSILON, f32::max);
+    for pixel in image.pixels_mut() {
+        let luminance = luminance(pixel) / max_luminance;
+        for (channel, component) in color.iter().enumerate() {
+            pixel[channel] = (component * luminance * 255.0).round().min(255.0) as u8;
+        }
+    }
+}
+
 type TextureContext = piston_window::G2dTextureContext;
 
 pub type Texture = piston_window::G2dTexture;
@@ -61,20 +104,47 @@ impl Assets {
         .expect(&format!("can not load the texture: {}", name))
     }
 
-    fn texture(&mut self, name: &str) -> rc::Rc<Texture> {
+    fn load_tinted_block_texture(&mut self, block: Block, face: BlockFace) -> Texture {
+        let name = self.block_texture_name(Block::new(TINT_BASE_BLOCK_NUMBER), face);
+        let mut image = image::open(self.path.join(&name))
+            .unwrap_or_else(|_| panic!("can not load the image: {}", name))
+            .to_rgba();
+        tint(&mut image, block_palette_color(block.number));
+        Texture::from_image(&mut self.texture_context, &image, &self.texture_settings)
+            .unwrap_or_else(|_| panic!("can not create a tinted texture for: {}", name))
+    }
+
+    fn cached_texture(
+        &mut self,
+        name: &str,
+        load: impl FnOnce(&mut Self) -> Texture,
+    ) -> rc::Rc<Texture> {
         if let Some(texture) = self.textures.get(name) {
             texture.clone()
         } else {
-            rc::Rc::new(self.load_texture(name))
+            let texture = rc::Rc::new(load(self));
+            self.textures.insert(name.to_owned(), texture.clone());
+            texture
         }
     }
 
+    fn texture(&mut self, name: &str) -> rc::Rc<Texture> {
+        self.cached_texture(name, |assets| assets.load_texture(name))
+    }
+
     fn block_texture_name(&self, block: Block, face: BlockFace) -> String {
         format!("block-{}-{}.png", block.number, face.name())
     }
 
     pub fn block_texture(&mut self, block: Block, face: BlockFace) -> rc::Rc<Texture> {
-        self.texture(&self.block_texture_name(block, face))
+        let name = self.block_texture_name(block, face);
+        self.cached_texture(&name, |assets| {
+            if assets.path.join(&name).exists() {
+                assets.load_texture(&name)
+            } else {
+                assets.load_tinted_block_texture(block, face)
+            }
+        })
     }
 
     pub fn ghost_block_texture(&mut self) -> rc::Rc<Texture> {
@@ -85,6 +155,14 @@ impl Assets {
         self.texture("bg-tile.png")
     }
 
+    pub fn text_texture(&mut self, lines: &[String]) -> rc::Rc<Texture> {
+        let image = pixel_font::render(lines);
+        rc::Rc::new(
+            Texture::from_image(&mut self.texture_context, &image, &self.texture_settings)
+                .expect("can not create a text texture"),
+        )
+    }
+
     pub fn empty_texture(&self) -> rc::Rc<Texture> {
         self.empty_texture.clone()
     }
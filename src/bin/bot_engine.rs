@@ -0,0 +1,37 @@
+use rust_awesome_puzzle_game_example::bot::protocol::{self, EngineMessage, HostMessage};
+use rust_awesome_puzzle_game_example::bot::{Bot, Evaluator, Weights};
+use rust_awesome_puzzle_game_example::game::PieceInput;
+use std::env;
+use std::io;
+use std::path;
+
+fn main() -> io::Result<()> {
+    let weights = match env::args().nth(1) {
+        Some(path) => Weights::load(path::Path::new(&path))?,
+        None => Weights::default(),
+    };
+    let bot = Bot::new(Evaluator::new(weights));
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    while let Some(message) = protocol::read_message(&mut input)? {
+        match message {
+            HostMessage::State(state) => {
+                let reply = match bot.decide(&state.to_game()?) {
+                    Some(decision) => EngineMessage::Placement {
+                        hold: decision.hold,
+                        cells: protocol::piece_state_cells(&decision.placement.piece_state),
+                    },
+                    None => EngineMessage::Inputs {
+                        hold: false,
+                        inputs: vec![PieceInput::HardDrop],
+                    },
+                };
+                protocol::write_message(&mut output, &reply)?;
+            }
+            HostMessage::GameOver { .. } => break,
+        }
+    }
+    Ok(())
+}
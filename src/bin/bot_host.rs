@@ -0,0 +1,68 @@
+use rust_awesome_puzzle_game_example::bot::protocol;
+use rust_awesome_puzzle_game_example::game::{Frames, Game, GameMode};
+use std::env;
+use std::io;
+use std::process;
+
+const QUEUE_LENGTH: usize = 5;
+const DEFAULT_FRAMES_PER_TURN: Frames = 30;
+
+const USAGE: &str =
+    "usage: bot_host [--mode NAME] [--frames N] <engine command> [engine arguments...]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    let names = GameMode::ALL.iter().map(|mode| mode.name());
+    eprintln!("modes: {}", names.collect::<Vec<_>>().join(", "));
+    process::exit(2)
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1).peekable();
+    let mut mode = GameMode::default();
+    let mut frames_per_turn = DEFAULT_FRAMES_PER_TURN;
+    while let Some(name) = args.peek().and_then(|arg| arg.strip_prefix("--")) {
+        let name = name.to_owned();
+        args.next();
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match name.as_str() {
+            "mode" => mode = GameMode::from_name(&value).unwrap_or_else(|| exit_with_usage()),
+            "frames" => frames_per_turn = value.parse().unwrap_or_else(|_| exit_with_usage()),
+            _ => exit_with_usage(),
+        }
+    }
+    let program = args.next().unwrap_or_else(|| exit_with_usage());
+    let mut engine = process::Command::new(program)
+        .args(args)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .spawn()?;
+    let mut engine_input = engine.stdin.take().unwrap();
+    let mut engine_output = io::BufReader::new(engine.stdout.take().unwrap());
+    let mut game = Game::with_mode(mode);
+    while !game.is_game_over() {
+        let state = protocol::StateMessage::from_game(&mut game, QUEUE_LENGTH);
+        protocol::write_message(&mut engine_input, &protocol::HostMessage::State(state))?;
+        let message = match protocol::read_message(&mut engine_output)? {
+            Some(message) => message,
+            None => break,
+        };
+        if protocol::apply_engine_message(&mut game, &message).is_none() {
+            eprintln!("the engine sent an invalid reply: {:?}", message);
+            break;
+        }
+        for _ in 0..frames_per_turn {
+            game.tick();
+        }
+    }
+    let pieces = game.stats().placed_pieces;
+    let lines = game.cleared_lines();
+    protocol::write_message(
+        &mut engine_input,
+        &protocol::HostMessage::GameOver { pieces, lines },
+    )?;
+    drop(engine_input);
+    engine.wait()?;
+    println!("pieces: {}, lines: {}", pieces, lines);
+    Ok(())
+}
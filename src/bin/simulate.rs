@@ -0,0 +1,108 @@
+use rust_awesome_puzzle_game_example::bot::optimizer::{self, OptimizerConfig};
+use rust_awesome_puzzle_game_example::bot::simulator::{self, SimulationConfig, Summary};
+use rust_awesome_puzzle_game_example::bot::Weights;
+use rust_awesome_puzzle_game_example::game::{piece_set, Game};
+use std::collections;
+use std::env;
+use std::io;
+use std::path;
+use std::process;
+use std::str;
+
+const USAGE: &str = "usage:
+    simulate run [--games N] [--pieces N] [--threads N] [--seed N] [--piece-set NAME|FILE]
+                 [--weights FILE]
+    simulate optimize [--generations N] [--population N] [--games N] [--pieces N]
+                      [--threads N] [--seed N] [--piece-set NAME|FILE] [--output FILE]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> collections::HashMap<String, String> {
+    let mut options = collections::HashMap::new();
+    while let Some(arg) = args.next() {
+        let name = match arg.strip_prefix("--") {
+            Some(name) => name.to_owned(),
+            None => exit_with_usage(),
+        };
+        match args.next() {
+            Some(value) => options.insert(name, value),
+            None => exit_with_usage(),
+        };
+    }
+    options
+}
+
+fn option<T: str::FromStr>(
+    options: &collections::HashMap<String, String>,
+    name: &str,
+    default: T,
+) -> T {
+    options.get(name).map_or(default, |value| {
+        value.parse().unwrap_or_else(|_| exit_with_usage())
+    })
+}
+
+fn simulation_config(options: &collections::HashMap<String, String>) -> SimulationConfig {
+    let default = SimulationConfig::default();
+    SimulationConfig {
+        games: option(options, "games", default.games),
+        max_pieces: option(options, "pieces", default.max_pieces),
+        threads: option(options, "threads", default.threads),
+        seed: option(options, "seed", default.seed),
+        piece_set: options.get("piece-set").map_or(default.piece_set, |name| {
+            piece_set::builtin_or_load(name, Game::default_stage_size()).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                process::exit(2)
+            })
+        }),
+    }
+}
+
+fn run(options: collections::HashMap<String, String>) -> io::Result<()> {
+    let weights = match options.get("weights") {
+        Some(path) => Weights::load(path::Path::new(path))?,
+        None => Weights::default(),
+    };
+    let results = simulator::simulate(weights, &simulation_config(&options));
+    println!("{}", Summary::new(&results));
+    Ok(())
+}
+
+fn optimize(options: collections::HashMap<String, String>) -> io::Result<()> {
+    let default = OptimizerConfig::default();
+    let config = OptimizerConfig {
+        generations: option(&options, "generations", default.generations),
+        population: option(&options, "population", default.population),
+        simulation: SimulationConfig {
+            games: option(&options, "games", default.simulation.games),
+            ..simulation_config(&options)
+        },
+        ..default
+    };
+    let output = options
+        .get("output")
+        .map_or("weights.json", |output| output.as_str());
+    let best = optimizer::optimize(config, path::Path::new(output), |generation, elite| {
+        println!(
+            "generation {}: {:.2} lines, {:?}",
+            generation, elite.summary.average_lines, elite.weights
+        );
+    })?;
+    if let Some(best) = best {
+        println!("{}", best.summary);
+        println!("the best weights were written to {}", output);
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("run") => run(parse_options(args)),
+        Some("optimize") => optimize(parse_options(args)),
+        _ => exit_with_usage(),
+    }
+}
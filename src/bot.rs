@@ -0,0 +1,105 @@
+pub mod evaluator;
+pub mod optimizer;
+pub mod protocol;
+pub mod simulator;
+
+pub use evaluator::{Evaluator, Weights};
+
+use crate::game::{Game, GameEvent, Placement};
+use std::cmp;
+use std::collections;
+
+pub fn cleared_line_count(events: &[GameEvent]) -> usize {
+    events
+        .iter()
+        .map(|event| match event {
+            GameEvent::RemoveBlocks(results) => results
+                .iter()
+                .map(|result| result.index.y)
+                .collect::<collections::HashSet<_>>()
+                .len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Decision {
+    pub hold: bool,
+    pub placement: Placement,
+}
+
+impl Decision {
+    pub fn apply(&self, game: &mut Game) -> Vec<GameEvent> {
+        let mut events = vec![];
+        if self.hold {
+            events.append(&mut game.hold_piece());
+        }
+        for &input in &self.placement.inputs {
+            events.append(&mut game.apply_piece_input(input));
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bot {
+    evaluator: Evaluator,
+}
+
+impl Bot {
+    pub fn new(evaluator: Evaluator) -> Self {
+        Self { evaluator }
+    }
+
+    fn evaluate_placement(&self, game: &Game, placement: &Placement) -> f64 {
+        let mut game = game.clone();
+        let mut events = vec![];
+        for &input in &placement.inputs {
+            events.append(&mut game.apply_piece_input(input));
+        }
+        if game.is_game_over() {
+            f64::NEG_INFINITY
+        } else {
+            self.evaluator
+                .evaluate(game.stage(), cleared_line_count(&events))
+        }
+    }
+
+    fn best_placement(&self, game: &Game) -> Option<(f64, Placement)> {
+        game.reachable_placements()
+            .into_iter()
+            .map(|placement| (self.evaluate_placement(game, &placement), placement))
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(cmp::Ordering::Equal))
+    }
+
+    pub fn decide(&self, game: &Game) -> Option<Decision> {
+        let mut candidates = vec![];
+        if let Some((score, placement)) = self.best_placement(game) {
+            candidates.push((
+                score,
+                Decision {
+                    hold: false,
+                    placement,
+                },
+            ));
+        }
+        if game.can_hold() {
+            let mut game = game.clone();
+            game.hold_piece();
+            if let Some((score, placement)) = self.best_placement(&game) {
+                candidates.push((
+                    score,
+                    Decision {
+                        hold: true,
+                        placement,
+                    },
+                ));
+            }
+        }
+        candidates
+            .into_iter()
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(cmp::Ordering::Equal))
+            .map(|(_, decision)| decision)
+    }
+}
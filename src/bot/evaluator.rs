@@ -0,0 +1,113 @@
+use crate::game::{BlockIndex, Stage};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weights {
+    pub aggregate_height: f64,
+    pub cleared_lines: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            aggregate_height: -0.510066,
+            cleared_lines: 0.760666,
+            holes: -0.35663,
+            bumpiness: -0.184483,
+        }
+    }
+}
+
+impl Weights {
+    pub fn to_array(self) -> [f64; 4] {
+        [
+            self.aggregate_height,
+            self.cleared_lines,
+            self.holes,
+            self.bumpiness,
+        ]
+    }
+
+    pub fn from_array(array: [f64; 4]) -> Self {
+        Self {
+            aggregate_height: array[0],
+            cleared_lines: array[1],
+            holes: array[2],
+            bumpiness: array[3],
+        }
+    }
+
+    pub fn load(path: &path::Path) -> io::Result<Self> {
+        Ok(serde_json::from_reader(io::BufReader::new(
+            fs::File::open(path)?,
+        ))?)
+    }
+
+    pub fn save(&self, path: &path::Path) -> io::Result<()> {
+        serde_json::to_writer_pretty(fs::File::create(path)?, self)?;
+        Ok(())
+    }
+}
+
+fn column_heights(stage: &Stage) -> Vec<usize> {
+    let size = stage.size();
+    (0..size.width)
+        .map(|x| {
+            (0..size.height)
+                .rev()
+                .find(|&y| stage.block(BlockIndex::new(x, y)).is_some())
+                .map_or(0, |y| y + 1)
+        })
+        .collect()
+}
+
+fn count_holes(stage: &Stage, heights: &[usize]) -> usize {
+    heights
+        .iter()
+        .enumerate()
+        .map(|(x, &height)| {
+            (0..height)
+                .filter(|&y| stage.block(BlockIndex::new(x, y)).is_none())
+                .count()
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    weights: Weights,
+}
+
+impl Evaluator {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+
+    pub fn weights(&self) -> Weights {
+        self.weights
+    }
+
+    pub fn evaluate(&self, stage: &Stage, cleared_lines: usize) -> f64 {
+        let heights = column_heights(stage);
+        let aggregate_height = heights.iter().sum::<usize>();
+        let bumpiness = heights
+            .windows(2)
+            .map(|pair| (pair[0] as isize - pair[1] as isize).unsigned_abs())
+            .sum::<usize>();
+        self.weights.aggregate_height * aggregate_height as f64
+            + self.weights.cleared_lines * cleared_lines as f64
+            + self.weights.holes * count_holes(stage, &heights) as f64
+            + self.weights.bumpiness * bumpiness as f64
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new(Weights::default())
+    }
+}
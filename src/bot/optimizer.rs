@@ -0,0 +1,117 @@
+use super::simulator::{self, SimulationConfig, Summary};
+use super::Weights;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp;
+use std::f64::consts::PI;
+use std::io;
+use std::path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimizerConfig {
+    pub generations: usize,
+    pub population: usize,
+    pub elite_ratio: f64,
+    pub initial_deviation: f64,
+    pub noise: f64,
+    pub simulation: SimulationConfig,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            generations: 20,
+            population: 32,
+            elite_ratio: 0.25,
+            initial_deviation: 0.5,
+            noise: 0.1,
+            simulation: SimulationConfig {
+                games: 20,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub weights: Weights,
+    pub summary: Summary,
+}
+
+impl Candidate {
+    fn fitness(&self) -> f64 {
+        self.summary.average_lines
+    }
+}
+
+fn sample_normal(rng: &mut impl Rng, mean: f64, deviation: f64) -> f64 {
+    let u1 = rng.gen_range(f64::EPSILON, 1.0);
+    let u2 = rng.gen::<f64>();
+    mean + deviation * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+pub fn optimize(
+    config: OptimizerConfig,
+    output: &path::Path,
+    mut report: impl FnMut(usize, &Candidate),
+) -> io::Result<Option<Candidate>> {
+    let mut rng = StdRng::seed_from_u64(config.simulation.seed);
+    let mut means = Weights::default().to_array();
+    let mut deviations = [config.initial_deviation; 4];
+    let elite_count = ((config.population as f64 * config.elite_ratio).ceil() as usize).max(1);
+    let mut best: Option<Candidate> = None;
+    for generation in 0..config.generations {
+        let simulation = SimulationConfig {
+            seed: config
+                .simulation
+                .seed
+                .wrapping_add((generation * config.simulation.games) as u64),
+            ..config.simulation.clone()
+        };
+        let mut candidates = (0..config.population)
+            .map(|_| {
+                let mut array = [0.0; 4];
+                for (i, value) in array.iter_mut().enumerate() {
+                    *value = sample_normal(&mut rng, means[i], deviations[i]);
+                }
+                let weights = Weights::from_array(array);
+                Candidate {
+                    weights,
+                    summary: Summary::new(&simulator::simulate(weights, &simulation)),
+                }
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| {
+            b.fitness()
+                .partial_cmp(&a.fitness())
+                .unwrap_or(cmp::Ordering::Equal)
+        });
+        let elites = &candidates[..elite_count.min(candidates.len())];
+        if elites.is_empty() {
+            break;
+        }
+        for (i, (mean, deviation)) in means.iter_mut().zip(deviations.iter_mut()).enumerate() {
+            let values = elites
+                .iter()
+                .map(|candidate| candidate.weights.to_array()[i])
+                .collect::<Vec<_>>();
+            *mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance = values
+                .iter()
+                .map(|value| (value - *mean).powi(2))
+                .sum::<f64>()
+                / values.len() as f64;
+            *deviation = variance.sqrt() + config.noise / (generation + 1) as f64;
+        }
+        report(generation, &elites[0]);
+        if best
+            .as_ref()
+            .is_none_or(|best| elites[0].fitness() > best.fitness())
+        {
+            elites[0].weights.save(output)?;
+            best = Some(elites[0].clone());
+        }
+    }
+    Ok(best)
+}
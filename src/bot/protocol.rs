@@ -0,0 +1,405 @@
+use crate::game::block::BlockNumber;
+use crate::game::stage;
+use crate::game::{
+    Block, BlockGridSize, BlockIndex, BlockIndexOffset, Frames, Game, GameEvent, Piece, PieceInput,
+    PieceSet, PieceState, Stage,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections;
+use std::error;
+use std::fmt;
+use std::io;
+
+const MAX_BOARD_HEIGHT: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BoardError {
+    InvalidWidth(usize),
+    InvalidHeight(usize),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::InvalidWidth(width) => write!(
+                f,
+                "the board is {} blocks wide but must be 1 to {} blocks wide",
+                width,
+                stage::MAX_WIDTH
+            ),
+            BoardError::InvalidHeight(height) => write!(
+                f,
+                "the board is {} blocks high but must be 1 to {} blocks high",
+                height, MAX_BOARD_HEIGHT
+            ),
+        }
+    }
+}
+
+impl error::Error for BoardError {}
+
+impl From<BoardError> for io::Error {
+    fn from(error: BoardError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PieceMessage {
+    pub number: BlockNumber,
+    pub size: [usize; 2],
+    pub cells: Vec<[usize; 2]>,
+}
+
+impl PieceMessage {
+    pub fn from_piece(piece: &Piece) -> Self {
+        Self {
+            number: piece.block().number,
+            size: [piece.size().width, piece.size().height],
+            cells: piece
+                .blocks()
+                .map(|(index, _)| [index.x, index.y])
+                .collect(),
+        }
+    }
+
+    pub fn to_piece(&self) -> Piece {
+        Piece::new(
+            BlockGridSize::new(self.size[0], self.size[1]),
+            Block::new(self.number),
+            self.cells
+                .iter()
+                .map(|&[x, y]| BlockIndex::new(x, y))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PieceStateMessage {
+    pub piece: PieceMessage,
+    pub position: [isize; 2],
+}
+
+impl PieceStateMessage {
+    pub fn from_piece_state(piece_state: &PieceState) -> Self {
+        Self {
+            piece: PieceMessage::from_piece(&piece_state.piece),
+            position: [piece_state.position.x, piece_state.position.y],
+        }
+    }
+
+    pub fn to_piece_state(&self) -> PieceState {
+        PieceState::new(
+            self.piece.to_piece(),
+            BlockIndexOffset::new(self.position[0], self.position[1]),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BoardMessage {
+    pub width: usize,
+    pub height: usize,
+    pub rows: Vec<Vec<Option<BlockNumber>>>,
+}
+
+impl BoardMessage {
+    pub fn from_stage(stage: &Stage) -> Self {
+        let size = stage.size();
+        Self {
+            width: size.width,
+            height: size.height,
+            rows: (0..size.height)
+                .map(|y| {
+                    (0..size.width)
+                        .map(|x| stage.block(BlockIndex::new(x, y)).map(|block| block.number))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_stage(&self) -> Result<Stage, BoardError> {
+        if self.width == 0 || self.width > stage::MAX_WIDTH {
+            return Err(BoardError::InvalidWidth(self.width));
+        }
+        if self.height == 0 || self.height > MAX_BOARD_HEIGHT {
+            return Err(BoardError::InvalidHeight(self.height));
+        }
+        let mut stage = Stage::new(BlockGridSize::new(self.width, self.height));
+        for (y, row) in self.rows.iter().take(self.height).enumerate() {
+            for (x, number) in row.iter().take(self.width).enumerate() {
+                if let Some(number) = number {
+                    stage.put_block(BlockIndex::new(x, y), Block::new(*number));
+                }
+            }
+        }
+        Ok(stage)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GarbageMessage {
+    pub frames_until_rise: Frames,
+    pub rise_interval: Frames,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StateMessage {
+    pub board: BoardMessage,
+    pub current: PieceStateMessage,
+    pub queue: Vec<PieceMessage>,
+    pub hold: Option<PieceMessage>,
+    pub can_hold: bool,
+    #[serde(default)]
+    pub garbage: Option<GarbageMessage>,
+}
+
+impl StateMessage {
+    pub fn from_game(game: &mut Game, queue_length: usize) -> Self {
+        Self {
+            board: BoardMessage::from_stage(game.stage()),
+            current: PieceStateMessage::from_piece_state(game.piece_state()),
+            queue: game
+                .next_pieces(queue_length)
+                .iter()
+                .map(PieceMessage::from_piece)
+                .collect(),
+            hold: game.held_piece().map(PieceMessage::from_piece),
+            can_hold: game.can_hold(),
+            garbage: if game.mode().raises_floor() {
+                Some(GarbageMessage {
+                    frames_until_rise: game.frames_until_rise(),
+                    rise_interval: game.rise_interval(),
+                })
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn to_game(&self) -> Result<Game, BoardError> {
+        let game = Game::from_position(
+            PieceSet::standard(),
+            self.board.to_stage()?,
+            self.current.to_piece_state(),
+            self.queue.iter().map(PieceMessage::to_piece).collect(),
+            self.hold.as_ref().map(PieceMessage::to_piece),
+            self.can_hold,
+        );
+        Ok(match &self.garbage {
+            Some(garbage) => {
+                game.with_rising_floor(garbage.frames_until_rise, garbage.rise_interval)
+            }
+            None => game,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostMessage {
+    State(StateMessage),
+    GameOver { pieces: usize, lines: usize },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineMessage {
+    Placement {
+        #[serde(default)]
+        hold: bool,
+        cells: Vec<[isize; 2]>,
+    },
+    Inputs {
+        #[serde(default)]
+        hold: bool,
+        inputs: Vec<PieceInput>,
+    },
+}
+
+pub fn piece_state_cells(piece_state: &PieceState) -> Vec<[isize; 2]> {
+    piece_state
+        .blocks()
+        .map(|(index, _)| [index.x, index.y])
+        .collect()
+}
+
+fn find_placement_inputs(game: &Game, cells: &[[isize; 2]]) -> Option<Vec<PieceInput>> {
+    let cells = cells.iter().collect::<collections::HashSet<_>>();
+    game.reachable_placements()
+        .into_iter()
+        .find(|placement| {
+            let placement_cells = piece_state_cells(&placement.piece_state);
+            placement_cells.len() == cells.len()
+                && placement_cells.iter().all(|cell| cells.contains(cell))
+        })
+        .map(|placement| placement.inputs)
+}
+
+pub fn apply_engine_message(game: &mut Game, message: &EngineMessage) -> Option<Vec<GameEvent>> {
+    let (hold, inputs) = match message {
+        EngineMessage::Placement { hold, cells } => {
+            let mut held_game = game.clone();
+            if *hold && held_game.hold_piece().is_empty() {
+                return None;
+            }
+            (*hold, find_placement_inputs(&held_game, cells)?)
+        }
+        EngineMessage::Inputs { hold, inputs } => (*hold, inputs.clone()),
+    };
+    let mut events = vec![];
+    if hold {
+        events.append(&mut game.hold_piece());
+        if events.is_empty() {
+            return None;
+        }
+    }
+    for input in inputs {
+        events.append(&mut game.apply_piece_input(input));
+    }
+    Some(events)
+}
+
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl io::BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            return Ok(Some(serde_json::from_str(&line)?));
+        }
+    }
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl io::Write, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameMode;
+
+    fn sample_game() -> Game {
+        let mut game = Game::new();
+        game.hold_piece();
+        game.drop_piece_hard();
+        game
+    }
+
+    #[test]
+    fn round_trips_states_through_json() {
+        let mut game = sample_game();
+        let state = StateMessage::from_game(&mut game, 5);
+        let mut buffer = vec![];
+        write_message(&mut buffer, &HostMessage::State(state.clone())).unwrap();
+        let message = read_message::<HostMessage>(&mut &buffer[..]).unwrap();
+        assert_eq!(message, Some(HostMessage::State(state.clone())));
+        assert_eq!(
+            StateMessage::from_game(&mut state.to_game().unwrap(), 5),
+            state
+        );
+    }
+
+    #[test]
+    fn reads_engine_messages_line_by_line() {
+        let source = concat!(
+            "{\"type\": \"inputs\", \"inputs\": [\"move_left\", \"hard_drop\"]}\n",
+            "\n",
+            "{\"type\": \"placement\", \"hold\": true, \"cells\": [[0, 0]]}\n",
+        );
+        let mut reader = source.as_bytes();
+        assert_eq!(
+            read_message::<EngineMessage>(&mut reader).unwrap(),
+            Some(EngineMessage::Inputs {
+                hold: false,
+                inputs: vec![PieceInput::MoveLeft, PieceInput::HardDrop],
+            })
+        );
+        assert_eq!(
+            read_message::<EngineMessage>(&mut reader).unwrap(),
+            Some(EngineMessage::Placement {
+                hold: true,
+                cells: vec![[0, 0]],
+            })
+        );
+        assert_eq!(read_message::<EngineMessage>(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn applies_placements_from_engines() {
+        let mut game = sample_game();
+        let placement = game.reachable_placements().pop().unwrap();
+        let cells = piece_state_cells(&placement.piece_state);
+        let message = EngineMessage::Placement { hold: false, cells };
+        let events = apply_engine_message(&mut game, &message).unwrap();
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::PutBlocks(_))));
+        let invalid = EngineMessage::Placement {
+            hold: false,
+            cells: vec![[-5, -5]],
+        };
+        assert!(apply_engine_message(&mut game, &invalid).is_none());
+    }
+
+    #[test]
+    fn rejects_boards_the_stage_can_not_hold() {
+        let board = |width, height| BoardMessage {
+            width,
+            height,
+            rows: vec![],
+        };
+        assert!(board(1, 1).to_stage().is_ok());
+        assert!(board(stage::MAX_WIDTH, MAX_BOARD_HEIGHT).to_stage().is_ok());
+        assert_eq!(
+            board(0, 20).to_stage().err(),
+            Some(BoardError::InvalidWidth(0))
+        );
+        assert_eq!(
+            board(stage::MAX_WIDTH + 1, 20).to_stage().err(),
+            Some(BoardError::InvalidWidth(stage::MAX_WIDTH + 1))
+        );
+        assert_eq!(
+            board(10, 0).to_stage().err(),
+            Some(BoardError::InvalidHeight(0))
+        );
+        assert_eq!(
+            board(10, MAX_BOARD_HEIGHT + 1).to_stage().err(),
+            Some(BoardError::InvalidHeight(MAX_BOARD_HEIGHT + 1))
+        );
+    }
+
+    #[test]
+    fn carries_garbage_timing_to_engines() {
+        let mut game = Game::with_mode(GameMode::Survival);
+        game.drop_piece_hard();
+        for _ in 0..10 {
+            game.tick();
+        }
+        let state = StateMessage::from_game(&mut game, 5);
+        let garbage = state.garbage.clone().unwrap();
+        assert_eq!(garbage.frames_until_rise, game.frames_until_rise());
+        assert_eq!(garbage.rise_interval, game.rise_interval());
+
+        let mut buffer = vec![];
+        write_message(&mut buffer, &HostMessage::State(state.clone())).unwrap();
+        let mut restored = match read_message::<HostMessage>(&mut &buffer[..]).unwrap() {
+            Some(HostMessage::State(state)) => state.to_game().unwrap(),
+            message => panic!("unexpected message: {:?}", message),
+        };
+        assert_eq!(StateMessage::from_game(&mut restored, 5), state);
+
+        let placement = restored.reachable_placements().pop().unwrap();
+        let cells = piece_state_cells(&placement.piece_state);
+        let message = EngineMessage::Placement { hold: false, cells };
+        assert!(apply_engine_message(&mut restored, &message).is_some());
+        assert_eq!(restored.frames_until_rise(), garbage.frames_until_rise);
+    }
+}
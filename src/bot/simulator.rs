@@ -0,0 +1,156 @@
+use super::{Bot, Evaluator, Weights};
+use crate::game::{Game, PieceSet};
+use std::fmt;
+use std::thread;
+
+const ATTACKS: [usize; 5] = [0, 0, 1, 2, 4];
+
+pub fn attack(cleared_lines: usize) -> usize {
+    ATTACKS[cleared_lines.min(ATTACKS.len() - 1)]
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SimulationConfig {
+    pub games: usize,
+    pub max_pieces: usize,
+    pub threads: usize,
+    pub seed: u64,
+    pub piece_set: PieceSet,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            games: 100,
+            max_pieces: 500,
+            threads: thread::available_parallelism().map_or(1, |count| count.get()),
+            seed: 0,
+            piece_set: PieceSet::standard(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct GameResult {
+    pub seed: u64,
+    pub pieces: usize,
+    pub lines: usize,
+    pub attack: usize,
+    pub score: u64,
+    pub topped_out: bool,
+}
+
+pub fn simulate_game(
+    weights: Weights,
+    piece_set: &PieceSet,
+    seed: u64,
+    max_pieces: usize,
+) -> GameResult {
+    let bot = Bot::new(Evaluator::new(weights));
+    let mut game = Game::with_seed(piece_set.clone(), seed);
+    let mut pieces = 0;
+    let mut total_attack = 0;
+    while pieces < max_pieces && !game.is_game_over() {
+        let decision = match bot.decide(&game) {
+            Some(decision) => decision,
+            None => break,
+        };
+        let events = decision.apply(&mut game);
+        pieces += 1;
+        total_attack += attack(super::cleared_line_count(&events));
+    }
+    GameResult {
+        seed,
+        pieces,
+        lines: game.cleared_lines(),
+        attack: total_attack,
+        score: game.score(),
+        topped_out: game.is_game_over(),
+    }
+}
+
+pub fn simulate(weights: Weights, config: &SimulationConfig) -> Vec<GameResult> {
+    let threads = config.threads.max(1);
+    let handles = (0..threads)
+        .map(|thread_index| {
+            let seeds = (thread_index..config.games)
+                .step_by(threads)
+                .map(|index| config.seed.wrapping_add(index as u64))
+                .collect::<Vec<_>>();
+            let piece_set = config.piece_set.clone();
+            let max_pieces = config.max_pieces;
+            thread::spawn(move || {
+                seeds
+                    .into_iter()
+                    .map(|seed| simulate_game(weights, &piece_set, seed, max_pieces))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut results = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().expect("a simulation thread panicked"))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|result| result.seed);
+    results
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub games: usize,
+    pub average_lines: f64,
+    pub average_pieces: f64,
+    pub attack_per_piece: f64,
+    pub top_out_rate: f64,
+    pub score_quartiles: [u64; 5],
+}
+
+impl Summary {
+    pub fn new(results: &[GameResult]) -> Self {
+        let games = results.len().max(1) as f64;
+        let pieces = results.iter().map(|result| result.pieces).sum::<usize>();
+        let attack = results.iter().map(|result| result.attack).sum::<usize>();
+        let mut scores = results
+            .iter()
+            .map(|result| result.score)
+            .collect::<Vec<_>>();
+        scores.sort_unstable();
+        let quartile = |index: usize| {
+            if scores.is_empty() {
+                0
+            } else {
+                scores[(scores.len() - 1) * index / 4]
+            }
+        };
+        Self {
+            games: results.len(),
+            average_lines: results.iter().map(|result| result.lines).sum::<usize>() as f64 / games,
+            average_pieces: pieces as f64 / games,
+            attack_per_piece: attack as f64 / pieces.max(1) as f64,
+            top_out_rate: results.iter().filter(|result| result.topped_out).count() as f64 / games,
+            score_quartiles: [
+                quartile(0),
+                quartile(1),
+                quartile(2),
+                quartile(3),
+                quartile(4),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "games: {}", self.games)?;
+        writeln!(f, "average lines: {:.2}", self.average_lines)?;
+        writeln!(f, "average pieces: {:.2}", self.average_pieces)?;
+        writeln!(f, "attack per piece: {:.3}", self.attack_per_piece)?;
+        writeln!(f, "top out rate: {:.1}%", self.top_out_rate * 100.0)?;
+        let [min, lower, median, upper, max] = self.score_quartiles;
+        write!(
+            f,
+            "score: min {} / 25% {} / median {} / 75% {} / max {}",
+            min, lower, median, upper, max
+        )
+    }
+}
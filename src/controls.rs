@@ -0,0 +1,458 @@
+mod input_mapper;
+mod player_input;
+
+pub use input_mapper::{ActionEvent, ControlInput, InputMapper};
+pub use player_input::PlayerInput;
+
+use crate::directories;
+use crate::game::{Frames, InitialAction};
+use serde::{Deserialize, Serialize};
+use std::collections;
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+
+const FILE_NAME: &str = "controls.json";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateLeft,
+    RotateRight,
+    Rotate180,
+    Hold,
+    Undo,
+    Redo,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Undo,
+        Action::Redo,
+        Action::Pause,
+    ];
+
+    pub fn name(self) -> &'static str {
+        use Action::*;
+        match self {
+            MoveLeft => "move left",
+            MoveRight => "move right",
+            SoftDrop => "soft drop",
+            HardDrop => "hard drop",
+            RotateLeft => "rotate left",
+            RotateRight => "rotate right",
+            Rotate180 => "rotate 180",
+            Hold => "hold",
+            Undo => "undo",
+            Redo => "redo",
+            Pause => "pause",
+        }
+    }
+
+    pub fn initial_action(self) -> Option<InitialAction> {
+        match self {
+            Action::RotateLeft => Some(InitialAction::RotateLeft),
+            Action::RotateRight => Some(InitialAction::RotateRight),
+            Action::Rotate180 => Some(InitialAction::Rotate180),
+            Action::Hold => Some(InitialAction::Hold),
+            _ => None,
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        use Action::*;
+        match self {
+            MoveLeft => &["Left"],
+            MoveRight => &["Right"],
+            SoftDrop => &["Down"],
+            HardDrop => &["Up"],
+            RotateLeft => &["Z"],
+            RotateRight => &["X"],
+            Rotate180 => &["A"],
+            Hold => &["C"],
+            Undo => &["U"],
+            Redo => &["R"],
+            Pause => &["P", "Escape"],
+        }
+    }
+
+    fn default_controller_buttons(self) -> &'static [&'static str] {
+        use Action::*;
+        match self {
+            MoveLeft => &["Button13", "Axis0-"],
+            MoveRight => &["Button14", "Axis0+"],
+            SoftDrop => &["Button12", "Axis1+"],
+            HardDrop => &["Button11"],
+            RotateLeft => &["Button1"],
+            RotateRight => &["Button0"],
+            Rotate180 => &["Button3"],
+            Hold => &["Button9", "Button10"],
+            Undo => &["Button2"],
+            Redo => &[],
+            Pause => &["Button6"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyMap {
+    bindings: collections::BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::with_default_keys(Action::default_keys)
+    }
+}
+
+impl KeyMap {
+    fn with_default_keys(default_keys: fn(Action) -> &'static [&'static str]) -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| {
+                    let keys = default_keys(action).iter().map(|&key| key.to_owned());
+                    (action, keys.collect())
+                })
+                .collect(),
+        }
+    }
+
+    pub fn controller_default() -> Self {
+        Self::with_default_keys(Action::default_controller_buttons)
+    }
+
+    fn fill_missing_actions(&mut self, defaults: &KeyMap) {
+        for (&action, keys) in &defaults.bindings {
+            self.bindings.entry(action).or_insert_with(|| keys.clone());
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[String] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn actions<'a>(&'a self, key: &'a str) -> impl Iterator<Item = Action> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |(_, keys)| keys.iter().any(|bound_key| bound_key == key))
+            .map(|(&action, _)| action)
+    }
+
+    pub fn bind(&mut self, action: Action, keys: Vec<String>) {
+        for bound_keys in self.bindings.values_mut() {
+            bound_keys.retain(|key| !keys.contains(key));
+        }
+        self.bindings.insert(action, keys);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handling {
+    pub das: Frames,
+    pub arr: Frames,
+    pub soft_drop_factor: u32,
+    pub input_buffer: Frames,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: 10,
+            arr: 2,
+            soft_drop_factor: 6,
+            input_buffer: 30,
+        }
+    }
+}
+
+impl Handling {
+    pub fn soft_drop_interval(self, gravity: Frames) -> Frames {
+        gravity
+            .checked_div(self.soft_drop_factor)
+            .unwrap_or(gravity)
+            .max(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControllerBindings {
+    pub deadzone: f64,
+    pub keys: KeyMap,
+    pub controllers: collections::BTreeMap<i32, KeyMap>,
+}
+
+impl Default for ControllerBindings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.5,
+            keys: KeyMap::controller_default(),
+            controllers: Default::default(),
+        }
+    }
+}
+
+impl ControllerBindings {
+    pub fn key_map(&self, id: i32) -> &KeyMap {
+        self.controllers.get(&id).unwrap_or(&self.keys)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    pub keys: KeyMap,
+    pub handling: Handling,
+    pub controller: ControllerBindings,
+}
+
+impl Controls {
+    pub fn load(path: &path::Path) -> io::Result<Self> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+        let mut controls: Self = serde_json::from_str(&source)?;
+        if controls.handling.soft_drop_factor == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the soft drop factor must be at least 1",
+            ));
+        }
+        controls.keys.fill_missing_actions(&KeyMap::default());
+        let controller = &mut controls.controller;
+        controller
+            .keys
+            .fill_missing_actions(&KeyMap::controller_default());
+        for key_map in controller.controllers.values_mut() {
+            key_map.fill_missing_actions(&controller.keys);
+        }
+        Ok(controls)
+    }
+
+    pub fn save(&self, path: &path::Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.write_all(b"\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Repeat {
+    Once(Action),
+    UntilBlocked(Action),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AutoRepeat {
+    handling: Handling,
+    shifts: Vec<Action>,
+    shift_frames: Frames,
+    soft_drops: usize,
+    soft_drop_frames: Frames,
+}
+
+impl AutoRepeat {
+    pub fn new(handling: Handling) -> Self {
+        Self {
+            handling,
+            ..Self::default()
+        }
+    }
+
+    pub fn press(&mut self, action: Action) {
+        match action {
+            Action::MoveLeft | Action::MoveRight => {
+                self.shifts.push(action);
+                self.shift_frames = 0;
+            }
+            Action::SoftDrop => {
+                self.soft_drops += 1;
+                self.soft_drop_frames = 0;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn release(&mut self, action: Action) {
+        match action {
+            Action::MoveLeft | Action::MoveRight => {
+                if let Some(index) = self.shifts.iter().rposition(|&shift| shift == action) {
+                    if index + 1 == self.shifts.len() {
+                        self.shift_frames = 0;
+                    }
+                    self.shifts.remove(index);
+                }
+            }
+            Action::SoftDrop => {
+                self.soft_drops = self.soft_drops.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.shifts.clear();
+        self.soft_drops = 0;
+    }
+
+    pub fn tick(&mut self, gravity: Frames) -> Vec<Repeat> {
+        let mut repeats = vec![];
+        if let Some(&shift) = self.shifts.last() {
+            self.shift_frames += 1;
+            if let Some(charged_frames) = self.shift_frames.checked_sub(self.handling.das) {
+                match charged_frames.checked_rem(self.handling.arr) {
+                    None => repeats.push(Repeat::UntilBlocked(shift)),
+                    Some(0) => repeats.push(Repeat::Once(shift)),
+                    Some(_) => {}
+                }
+            }
+        }
+        if self.soft_drops > 0 {
+            self.soft_drop_frames += 1;
+            if self.soft_drop_frames >= self.handling.soft_drop_interval(gravity) {
+                self.soft_drop_frames = 0;
+                repeats.push(Repeat::Once(Action::SoftDrop));
+            }
+        }
+        repeats
+    }
+}
+
+pub fn default_path() -> Option<path::PathBuf> {
+    directories::config_directory().map(|directory| directory.join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn repeat_frames(
+        auto_repeat: &mut AutoRepeat,
+        gravity: Frames,
+        frames: Frames,
+    ) -> Vec<(Frames, Repeat)> {
+        (1..=frames)
+            .flat_map(|frame| {
+                let repeats = auto_repeat.tick(gravity);
+                repeats.into_iter().map(move |repeat| (frame, repeat))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeats_shifts_after_das_every_arr_frames() {
+        let mut auto_repeat = AutoRepeat::new(Handling {
+            das: 10,
+            arr: 2,
+            ..Handling::default()
+        });
+        auto_repeat.press(Action::MoveLeft);
+        let shift = Repeat::Once(Action::MoveLeft);
+        assert_eq!(
+            repeat_frames(&mut auto_repeat, 60, 15),
+            vec![(10, shift), (12, shift), (14, shift)]
+        );
+    }
+
+    #[test]
+    fn shifts_until_blocked_with_zero_arr() {
+        let mut auto_repeat = AutoRepeat::new(Handling {
+            das: 3,
+            arr: 0,
+            ..Handling::default()
+        });
+        auto_repeat.press(Action::MoveRight);
+        let shift = Repeat::UntilBlocked(Action::MoveRight);
+        assert_eq!(
+            repeat_frames(&mut auto_repeat, 60, 4),
+            vec![(3, shift), (4, shift)]
+        );
+    }
+
+    #[test]
+    fn recharges_das_for_the_latest_direction() {
+        let mut auto_repeat = AutoRepeat::new(Handling {
+            das: 3,
+            arr: 1,
+            ..Handling::default()
+        });
+        auto_repeat.press(Action::MoveLeft);
+        repeat_frames(&mut auto_repeat, 60, 5);
+        auto_repeat.press(Action::MoveRight);
+        let shift = Repeat::Once(Action::MoveRight);
+        assert_eq!(
+            repeat_frames(&mut auto_repeat, 60, 4),
+            vec![(3, shift), (4, shift)]
+        );
+        auto_repeat.release(Action::MoveRight);
+        let shift = Repeat::Once(Action::MoveLeft);
+        assert_eq!(repeat_frames(&mut auto_repeat, 60, 3), vec![(3, shift)]);
+    }
+
+    #[test]
+    fn soft_drops_at_a_fraction_of_the_gravity_interval() {
+        let mut auto_repeat = AutoRepeat::new(Handling {
+            soft_drop_factor: 6,
+            ..Handling::default()
+        });
+        auto_repeat.press(Action::SoftDrop);
+        let drop = Repeat::Once(Action::SoftDrop);
+        assert_eq!(
+            repeat_frames(&mut auto_repeat, 60, 25),
+            vec![(10, drop), (20, drop)]
+        );
+        auto_repeat.release(Action::SoftDrop);
+        assert_eq!(repeat_frames(&mut auto_repeat, 60, 10), vec![]);
+    }
+
+    #[test]
+    fn soft_drops_at_most_once_per_frame() {
+        let handling = Handling {
+            soft_drop_factor: 6,
+            ..Handling::default()
+        };
+        assert_eq!(handling.soft_drop_interval(3), 1);
+        assert_eq!(handling.soft_drop_interval(0), 1);
+        let mut auto_repeat = AutoRepeat::new(handling);
+        auto_repeat.press(Action::SoftDrop);
+        let drop = Repeat::Once(Action::SoftDrop);
+        assert_eq!(
+            repeat_frames(&mut auto_repeat, 3, 3),
+            vec![(1, drop), (2, drop), (3, drop)]
+        );
+    }
+
+    #[test]
+    fn rejects_a_zero_soft_drop_factor() {
+        let directory = env::temp_dir().join(format!("controls-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(FILE_NAME);
+        fs::write(&path, r#"{"handling": {"soft_drop_factor": 0}}"#).unwrap();
+        let error = Controls::load(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
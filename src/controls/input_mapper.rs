@@ -0,0 +1,235 @@
+use super::{Action, Controls, KeyMap};
+use std::collections;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlInput {
+    Key {
+        name: String,
+        is_pressed: bool,
+    },
+    ControllerButton {
+        id: i32,
+        button: u8,
+        is_pressed: bool,
+    },
+    ControllerAxis {
+        id: i32,
+        axis: u8,
+        position: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ActionEvent {
+    Press(Action),
+    Release(Action),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+struct HeldInput {
+    controller: Option<i32>,
+    name: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InputMapper {
+    controls: Controls,
+    held_inputs: collections::HashSet<HeldInput>,
+    axis_directions: collections::HashMap<(i32, u8), &'static str>,
+}
+
+impl InputMapper {
+    pub fn new(controls: Controls) -> Self {
+        Self {
+            controls,
+            ..Self::default()
+        }
+    }
+
+    pub fn controls(&self) -> &Controls {
+        &self.controls
+    }
+
+    pub fn controls_mut(&mut self) -> &mut Controls {
+        &mut self.controls
+    }
+
+    fn key_map(&self, controller: Option<i32>) -> &KeyMap {
+        match controller {
+            Some(id) => self.controls.controller.key_map(id),
+            None => &self.controls.keys,
+        }
+    }
+
+    fn change_input(&mut self, held_input: HeldInput, is_pressed: bool) -> Vec<ActionEvent> {
+        let actions = self
+            .key_map(held_input.controller)
+            .actions(&held_input.name)
+            .collect::<Vec<_>>();
+        let is_changed = if is_pressed {
+            self.held_inputs.insert(held_input)
+        } else {
+            self.held_inputs.remove(&held_input)
+        };
+        if !is_changed {
+            return vec![];
+        }
+        actions
+            .into_iter()
+            .map(|action| {
+                if is_pressed {
+                    ActionEvent::Press(action)
+                } else {
+                    ActionEvent::Release(action)
+                }
+            })
+            .collect()
+    }
+
+    fn axis_direction(&self, position: f64) -> &'static str {
+        if position > self.controls.controller.deadzone {
+            "+"
+        } else if position < -self.controls.controller.deadzone {
+            "-"
+        } else {
+            ""
+        }
+    }
+
+    fn move_axis(&mut self, id: i32, axis: u8, position: f64) -> Vec<ActionEvent> {
+        let direction = self.axis_direction(position);
+        let previous_direction = self
+            .axis_directions
+            .insert((id, axis), direction)
+            .unwrap_or("");
+        if direction == previous_direction {
+            return vec![];
+        }
+        let axis_input = |direction| HeldInput {
+            controller: Some(id),
+            name: format!("Axis{}{}", axis, direction),
+        };
+        let mut events = vec![];
+        if !previous_direction.is_empty() {
+            events.append(&mut self.change_input(axis_input(previous_direction), false));
+        }
+        if !direction.is_empty() {
+            events.append(&mut self.change_input(axis_input(direction), true));
+        }
+        events
+    }
+
+    pub fn apply(&mut self, input: ControlInput) -> Vec<ActionEvent> {
+        match input {
+            ControlInput::Key { name, is_pressed } => self.change_input(
+                HeldInput {
+                    controller: None,
+                    name,
+                },
+                is_pressed,
+            ),
+            ControlInput::ControllerButton {
+                id,
+                button,
+                is_pressed,
+            } => self.change_input(
+                HeldInput {
+                    controller: Some(id),
+                    name: format!("Button{}", button),
+                },
+                is_pressed,
+            ),
+            ControlInput::ControllerAxis { id, axis, position } => {
+                self.move_axis(id, axis, position)
+            }
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.held_inputs.clear();
+        self.axis_directions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(id: i32, position: f64) -> ControlInput {
+        ControlInput::ControllerAxis {
+            id,
+            axis: 0,
+            position,
+        }
+    }
+
+    fn button(id: i32, button: u8, is_pressed: bool) -> ControlInput {
+        ControlInput::ControllerButton {
+            id,
+            button,
+            is_pressed,
+        }
+    }
+
+    #[test]
+    fn flips_axis_directions() {
+        let mut mapper = InputMapper::default();
+        assert_eq!(
+            mapper.apply(axis(0, -1.0)),
+            vec![ActionEvent::Press(Action::MoveLeft)]
+        );
+        assert_eq!(
+            mapper.apply(axis(0, 1.0)),
+            vec![
+                ActionEvent::Release(Action::MoveLeft),
+                ActionEvent::Press(Action::MoveRight)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_axis_positions_in_the_deadzone() {
+        let mut mapper = InputMapper::default();
+        assert!(mapper.apply(axis(0, 0.3)).is_empty());
+        assert!(mapper.apply(axis(0, -0.5)).is_empty());
+        assert_eq!(
+            mapper.apply(axis(0, 0.8)),
+            vec![ActionEvent::Press(Action::MoveRight)]
+        );
+        assert_eq!(
+            mapper.apply(axis(0, 0.2)),
+            vec![ActionEvent::Release(Action::MoveRight)]
+        );
+    }
+
+    #[test]
+    fn ignores_repeated_presses() {
+        let mut mapper = InputMapper::default();
+        assert_eq!(
+            mapper.apply(button(0, 0, true)),
+            vec![ActionEvent::Press(Action::RotateRight)]
+        );
+        assert!(mapper.apply(button(0, 0, true)).is_empty());
+        assert_eq!(
+            mapper.apply(button(0, 0, false)),
+            vec![ActionEvent::Release(Action::RotateRight)]
+        );
+    }
+
+    #[test]
+    fn uses_per_controller_bindings() {
+        let mut controls = Controls::default();
+        let mut key_map = controls.controller.keys.clone();
+        key_map.bind(Action::Hold, vec!["Button0".to_owned()]);
+        controls.controller.controllers.insert(1, key_map);
+        let mut mapper = InputMapper::new(controls);
+        assert_eq!(
+            mapper.apply(button(1, 0, true)),
+            vec![ActionEvent::Press(Action::Hold)]
+        );
+        assert_eq!(
+            mapper.apply(button(0, 0, true)),
+            vec![ActionEvent::Press(Action::RotateRight)]
+        );
+    }
+}
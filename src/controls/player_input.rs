@@ -0,0 +1,343 @@
+use super::{Action, ActionEvent, AutoRepeat, ControlInput, Controls, InputMapper, Repeat};
+use crate::game::{Frames, Game, GameEvent, GameMode};
+use std::collections;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct BufferedAction {
+    action: Action,
+    frames: Frames,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlayerInput {
+    input_mapper: InputMapper,
+    auto_repeat: AutoRepeat,
+    buffered_actions: collections::VecDeque<BufferedAction>,
+    is_paused: bool,
+}
+
+impl PlayerInput {
+    pub fn new(controls: Controls) -> Self {
+        Self {
+            auto_repeat: AutoRepeat::new(controls.handling),
+            input_mapper: InputMapper::new(controls),
+            ..Self::default()
+        }
+    }
+
+    pub fn controls(&self) -> &Controls {
+        self.input_mapper.controls()
+    }
+
+    pub fn controls_mut(&mut self) -> &mut Controls {
+        self.input_mapper.controls_mut()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    fn can_apply_action(game: &Game, action: Action) -> bool {
+        let is_history_action = matches!(action, Action::Undo | Action::Redo);
+        (is_history_action || !game.is_game_over()) && game.phase().has_piece()
+    }
+
+    fn action_events(game: &mut Game, action: Action) -> Vec<GameEvent> {
+        use Action::*;
+        let is_practice = game.mode() == GameMode::Practice;
+        match action {
+            MoveLeft => game.move_piece_left(),
+            MoveRight => game.move_piece_right(),
+            SoftDrop => game.drop_piece_soft(),
+            HardDrop => game.drop_piece_hard(),
+            RotateLeft => game.rotate_piece_left(),
+            RotateRight => game.rotate_piece_right(),
+            Rotate180 => game.rotate_piece_180(),
+            Hold => game.hold_piece(),
+            Undo if is_practice => game.undo(),
+            Redo if is_practice => game.redo(),
+            Undo | Redo | Pause => vec![],
+        }
+    }
+
+    fn apply_buffered_actions(&mut self, game: &mut Game) -> Vec<GameEvent> {
+        let mut events = vec![];
+        while let Some(buffered_action) = self.buffered_actions.front() {
+            let action = buffered_action.action;
+            if !Self::can_apply_action(game, action) {
+                break;
+            }
+            self.buffered_actions.pop_front();
+            let is_applied_on_spawn = action
+                .initial_action()
+                .is_some_and(|initial_action| game.is_initial_action_held(initial_action));
+            if !is_applied_on_spawn {
+                events.append(&mut Self::action_events(game, action));
+            }
+        }
+        events
+    }
+
+    fn age_buffered_actions(&mut self) {
+        let input_buffer = self.controls().handling.input_buffer;
+        for buffered_action in &mut self.buffered_actions {
+            buffered_action.frames += 1;
+        }
+        self.buffered_actions
+            .retain(|buffered_action| buffered_action.frames < input_buffer);
+    }
+
+    fn repeat_action(game: &mut Game, action: Action) -> Vec<GameEvent> {
+        match action {
+            Action::MoveLeft => game.repeat_move_piece_left(),
+            Action::MoveRight => game.repeat_move_piece_right(),
+            Action::SoftDrop => game.repeat_drop_piece_soft(),
+            _ => vec![],
+        }
+    }
+
+    fn apply_repeat(game: &mut Game, repeat: Repeat) -> Vec<GameEvent> {
+        match repeat {
+            Repeat::Once(action) => Self::repeat_action(game, action),
+            Repeat::UntilBlocked(action) => {
+                let mut events = vec![];
+                loop {
+                    let mut repeated_events = Self::repeat_action(game, action);
+                    if repeated_events.is_empty() {
+                        break events;
+                    }
+                    events.append(&mut repeated_events);
+                }
+            }
+        }
+    }
+
+    fn apply_action(&mut self, game: &mut Game, action: Action) -> Vec<GameEvent> {
+        let mut events = self.apply_buffered_actions(game);
+        if Self::can_apply_action(game, action) {
+            events.append(&mut Self::action_events(game, action));
+        } else {
+            self.buffered_actions
+                .push_back(BufferedAction { action, frames: 0 });
+        }
+        events
+    }
+
+    fn apply_action_event(&mut self, game: &mut Game, event: ActionEvent) -> Vec<GameEvent> {
+        match event {
+            ActionEvent::Press(action) => {
+                if let Some(initial_action) = action.initial_action() {
+                    game.set_initial_action_held(initial_action, true);
+                }
+                self.auto_repeat.press(action);
+                if action == Action::Pause {
+                    self.is_paused = !self.is_paused;
+                    vec![]
+                } else if self.is_paused {
+                    vec![]
+                } else {
+                    self.apply_action(game, action)
+                }
+            }
+            ActionEvent::Release(action) => {
+                if let Some(initial_action) = action.initial_action() {
+                    game.set_initial_action_held(initial_action, false);
+                }
+                self.auto_repeat.release(action);
+                vec![]
+            }
+        }
+    }
+
+    pub fn input(&mut self, game: &mut Game, input: ControlInput) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for event in self.input_mapper.apply(input) {
+            events.append(&mut self.apply_action_event(game, event));
+        }
+        events
+    }
+
+    pub fn tick(&mut self, game: &mut Game) -> Vec<GameEvent> {
+        let mut events = game.tick();
+        events.append(&mut self.apply_buffered_actions(game));
+        for repeat in self.auto_repeat.tick(game.timings().gravity) {
+            events.append(&mut Self::apply_repeat(game, repeat));
+        }
+        self.age_buffered_actions();
+        events
+    }
+
+    pub fn release_all(&mut self, game: &mut Game) {
+        self.buffered_actions.clear();
+        self.input_mapper.release_all();
+        self.auto_repeat.release_all();
+        for action in Action::ALL
+            .iter()
+            .filter_map(|action| action.initial_action())
+        {
+            game.set_initial_action_held(action, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::Handling;
+    use crate::game::{InitialAction, Timings};
+
+    fn key(name: &str, is_pressed: bool) -> ControlInput {
+        ControlInput::Key {
+            name: name.to_owned(),
+            is_pressed,
+        }
+    }
+
+    fn button(button: u8, is_pressed: bool) -> ControlInput {
+        ControlInput::ControllerButton {
+            id: 0,
+            button,
+            is_pressed,
+        }
+    }
+
+    fn piece_x(game: &Game) -> isize {
+        game.piece_state().position.x
+    }
+
+    fn waiting_game() -> Game {
+        let mut game = Game::new().with_timings(Timings {
+            entry: 6,
+            ..Timings::default()
+        });
+        game.drop_piece_hard();
+        assert!(!game.phase().has_piece());
+        game
+    }
+
+    fn spawned(mut game: Game) -> Game {
+        while !game.phase().has_piece() {
+            game.tick();
+        }
+        game
+    }
+
+    fn tick_until_spawned(player_input: &mut PlayerInput, game: &mut Game) {
+        while !game.phase().has_piece() {
+            player_input.tick(game);
+        }
+    }
+
+    fn tap(player_input: &mut PlayerInput, game: &mut Game, name: &str) -> Vec<GameEvent> {
+        let events = player_input.input(game, key(name, true));
+        player_input.input(game, key(name, false));
+        events
+    }
+
+    #[test]
+    fn moves_pieces_with_keys_and_controllers() {
+        let mut game = Game::new();
+        let mut player_input = PlayerInput::default();
+        let x = piece_x(&game);
+        assert!(!player_input.input(&mut game, key("Left", true)).is_empty());
+        assert_eq!(piece_x(&game), x - 1);
+        player_input.input(&mut game, key("Left", false));
+        player_input.input(&mut game, button(14, true));
+        assert_eq!(piece_x(&game), x);
+        player_input.input(&mut game, button(14, false));
+        let axis = ControlInput::ControllerAxis {
+            id: 0,
+            axis: 0,
+            position: 1.0,
+        };
+        player_input.input(&mut game, axis);
+        assert_eq!(piece_x(&game), x + 1);
+    }
+
+    #[test]
+    fn repeats_held_moves_on_ticks() {
+        let mut game = Game::new();
+        let mut player_input = PlayerInput::new(Controls {
+            handling: Handling {
+                das: 5,
+                arr: 0,
+                ..Handling::default()
+            },
+            ..Controls::default()
+        });
+        player_input.input(&mut game, key("Right", true));
+        let x = piece_x(&game);
+        for _ in 0..4 {
+            player_input.tick(&mut game);
+        }
+        assert_eq!(piece_x(&game), x);
+        player_input.tick(&mut game);
+        assert!(piece_x(&game) > x);
+        assert!(game.move_piece_right().is_empty());
+    }
+
+    #[test]
+    fn buffers_actions_until_a_piece_can_be_controlled() {
+        let mut game = waiting_game();
+        let mut expected = spawned(game.clone());
+        expected.move_piece_left();
+        let mut player_input = PlayerInput::default();
+        assert!(tap(&mut player_input, &mut game, "Left").is_empty());
+        tick_until_spawned(&mut player_input, &mut game);
+        assert_eq!(game.piece_state(), expected.piece_state());
+    }
+
+    #[test]
+    fn applies_buffered_actions_in_order() {
+        let mut game = waiting_game();
+        let mut expected = spawned(game.clone());
+        expected.hold_piece();
+        expected.move_piece_left();
+        let mut reversed = spawned(game.clone());
+        reversed.move_piece_left();
+        reversed.hold_piece();
+        assert_ne!(expected.piece_state(), reversed.piece_state());
+        let mut player_input = PlayerInput::default();
+        tap(&mut player_input, &mut game, "C");
+        tap(&mut player_input, &mut game, "Left");
+        tick_until_spawned(&mut player_input, &mut game);
+        assert_eq!(game.piece_state(), expected.piece_state());
+        assert_eq!(game.held_piece(), expected.held_piece());
+    }
+
+    #[test]
+    fn drops_actions_older_than_the_input_buffer() {
+        let mut game = waiting_game();
+        let expected = spawned(game.clone());
+        let mut player_input = PlayerInput::new(Controls {
+            handling: Handling {
+                input_buffer: 3,
+                ..Handling::default()
+            },
+            ..Controls::default()
+        });
+        tap(&mut player_input, &mut game, "Left");
+        tick_until_spawned(&mut player_input, &mut game);
+        assert_eq!(game.piece_state(), expected.piece_state());
+    }
+
+    #[test]
+    fn does_not_repeat_initial_actions_held_through_the_spawn() {
+        let initial_actions = [
+            ("X", InitialAction::RotateRight),
+            ("C", InitialAction::Hold),
+        ];
+        for &(name, initial_action) in &initial_actions {
+            let mut game = waiting_game();
+            let mut expected = game.clone();
+            expected.set_initial_action_held(initial_action, true);
+            let expected = spawned(expected);
+            let mut player_input = PlayerInput::default();
+            player_input.input(&mut game, key(name, true));
+            tick_until_spawned(&mut player_input, &mut game);
+            assert_eq!(game.piece_state(), expected.piece_state(), "{}", name);
+            assert_eq!(game.held_piece(), expected.held_piece(), "{}", name);
+            assert_eq!(game.stats().key_presses, expected.stats().key_presses);
+        }
+    }
+}
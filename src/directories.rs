@@ -0,0 +1,34 @@
+use std::env;
+use std::path;
+
+const APPLICATION_NAME: &str = "rust-awesome-puzzle-game-example";
+
+fn home_directory(relative_path: &str) -> Option<path::PathBuf> {
+    env::var_os("HOME").map(|home| path::Path::new(&home).join(relative_path))
+}
+
+pub fn data_directory() -> Option<path::PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(path::PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_directory("Library/Application Support")
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(path::PathBuf::from)
+            .or_else(|| home_directory(".local/share"))
+    }
+    .map(|directory| directory.join(APPLICATION_NAME))
+}
+
+pub fn config_directory() -> Option<path::PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(path::PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_directory("Library/Preferences")
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(path::PathBuf::from)
+            .or_else(|| home_directory(".config"))
+    }
+    .map(|directory| directory.join(APPLICATION_NAME))
+}
@@ -0,0 +1,161 @@
+use crate::game::{Game, GameEvent, SubscriptionId};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+use std::time;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub timestamp: u64,
+    pub event: GameEvent,
+}
+
+impl EventRecord {
+    pub fn now(event: GameEvent) -> Self {
+        Self {
+            timestamp: time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_millis() as u64),
+            event,
+        }
+    }
+}
+
+pub struct EventLog {
+    writer: Box<dyn Write + Send>,
+}
+
+impl EventLog {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    pub fn create(path: &path::Path) -> io::Result<Self> {
+        Ok(Self::new(io::BufWriter::new(fs::File::create(path)?)))
+    }
+
+    pub fn open(name: &str) -> io::Result<Self> {
+        if name == "-" {
+            Ok(Self::stdout())
+        } else {
+            Self::create(path::Path::new(name))
+        }
+    }
+
+    pub fn write_event(&mut self, event: &GameEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &EventRecord::now(event.clone()))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    pub fn subscribe(mut self, game: &mut Game) -> SubscriptionId {
+        let mut is_broken = false;
+        game.subscribe(move |event| {
+            if is_broken {
+                return;
+            }
+            if let Err(error) = self.write_event(event) {
+                eprintln!("can not write the event log: {}", error);
+                is_broken = true;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Block, BlockIndex, MoveResult, RemoveResult};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn played_events() -> Vec<GameEvent> {
+        let mut game = Game::new();
+        let mut events = game.initial_events();
+        events.append(&mut game.hold_piece());
+        events.append(&mut game.move_piece_left());
+        events.append(&mut game.rotate_piece_right());
+        while !game.is_game_over() {
+            events.append(&mut game.drop_piece_hard());
+            events.append(&mut game.tick());
+        }
+        events
+    }
+
+    #[test]
+    fn round_trips_game_events_through_json() {
+        let block = Block::new(1);
+        let mut events = played_events();
+        events.extend(vec![
+            GameEvent::RemoveBlocks(vec![RemoveResult {
+                block,
+                index: BlockIndex::new(1, 2),
+            }]),
+            GameEvent::MoveBlocks(vec![MoveResult {
+                block,
+                source: BlockIndex::new(1, 2),
+                destination: BlockIndex::new(1, 0),
+            }]),
+            GameEvent::Chain(2),
+            GameEvent::FinesseFault {
+                expected: 2,
+                actual: 3,
+            },
+        ]);
+        for event in events {
+            let json = serde_json::to_string(&event).unwrap();
+            let parsed: GameEvent = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, event, "{}", json);
+        }
+    }
+
+    #[test]
+    fn writes_one_timestamped_record_per_line() {
+        let buffer = SharedBuffer::default();
+        let mut game = Game::new();
+        let before = EventRecord::now(GameEvent::GameOver).timestamp;
+        EventLog::new(buffer.clone()).subscribe(&mut game);
+        let events = game.drop_piece_hard();
+        let after = EventRecord::now(GameEvent::GameOver).timestamp;
+        let contents = buffer.contents();
+        assert!(contents.ends_with('\n'));
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<EventRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        for record in &records {
+            assert!(before <= record.timestamp && record.timestamp <= after);
+        }
+        let logged_events = records.into_iter().map(|record| record.event);
+        assert_eq!(logged_events.collect::<Vec<_>>(), events);
+        let first_line = contents.lines().next().unwrap();
+        let value: serde_json::Value = serde_json::from_str(first_line).unwrap();
+        assert!(value["timestamp"].is_u64());
+        assert!(value["event"]["type"].is_string());
+    }
+}
@@ -1,20 +1,50 @@
 pub mod block;
 pub mod event;
+mod finesse;
+mod history;
+pub mod initial_action;
+pub mod mode;
+pub mod phase;
 pub mod piece;
 mod piece_generator;
+pub mod piece_set;
+pub mod placement;
+pub mod polyomino;
+mod rising_floor;
 pub mod stage;
+pub mod stats;
+pub mod subscription;
+pub mod timing;
 
 pub use block::{Block, BlockGrid, BlockGridSize, BlockIndex, BlockIndexOffset, BlockSpace};
 pub use event::{GameEvent, MoveResult, PutResult, RemoveResult};
+pub use initial_action::InitialAction;
+pub use mode::GameMode;
+pub use phase::Phase;
 pub use piece::Piece;
+pub use piece_set::PieceSet;
+pub use placement::{PieceInput, Placement};
 pub use stage::Stage;
+pub use stats::GameStats;
+pub use subscription::SubscriptionId;
+pub use timing::{Frames, Timings, FRAME_RATE};
 
+use history::History;
 use piece_generator::PieceGenerator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections;
 use std::iter;
+use std::num;
+use subscription::Subscribers;
 
 const WIDTH: usize = 10;
 const HEIGHT: usize = 20;
-const WAIT: f64 = 0.2;
+const LINE_SCORES: [u64; 5] = [0, 100, 300, 500, 800];
+const MATCHED_BLOCK_SCORE: u64 = 10;
+const DEFAULT_MATCH_SIZE: usize = 8;
+const NO_KICKS: [(isize, isize); 1] = [(0, 0)];
+const ROTATION_180_KICKS: [(isize, isize); 6] = [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)];
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct PieceState {
@@ -29,13 +59,13 @@ impl PieceState {
 
     pub fn with_initial_position(piece: Piece, stage_size: BlockGridSize) -> Self {
         let position = BlockIndexOffset::new(
-            ((stage_size.width - piece.size().width) / 2) as isize,
+            (stage_size.width as isize - piece.size().width as isize) / 2,
             stage_size.height as isize - piece.size().height as isize / 2,
-        );
+        ) + piece.spawn_offset();
         Self::new(piece, position)
     }
 
-    fn blocks<'a>(&'a self) -> impl iter::Iterator<Item = (BlockIndexOffset, Block)> + 'a {
+    pub fn blocks<'a>(&'a self) -> impl iter::Iterator<Item = (BlockIndexOffset, Block)> + 'a {
         self.piece
             .blocks()
             .map(move |(index, block)| (self.position + index.cast::<isize>().to_vector(), block))
@@ -44,48 +74,286 @@ impl PieceState {
 
 #[derive(Debug, Clone)]
 pub struct Game {
+    mode: GameMode,
     stage: Stage,
     piece_state: PieceState,
+    spawned_piece: Piece,
+    held_piece: Option<Piece>,
+    can_hold: bool,
     piece_generator: PieceGenerator,
-    wait: f64,
+    wait: Frames,
+    phase: Phase,
+    timings: Timings,
+    match_size: usize,
+    cleared_line_indices: Vec<usize>,
+    chain: usize,
+    max_chain: usize,
+    score: u64,
+    cleared_lines: usize,
+    rise_wait: Frames,
+    rise_interval: Frames,
+    rng: StdRng,
+    is_game_over: bool,
+    stats: GameStats,
+    piece_key_presses: usize,
+    counted_shift: Option<isize>,
+    held_initial_actions: collections::HashSet<InitialAction>,
+    history: History,
+    subscribers: Subscribers,
 }
 
 impl Game {
+    fn with_piece_state(
+        stage: Stage,
+        piece_state: PieceState,
+        piece_generator: PieceGenerator,
+    ) -> Self {
+        let mut game = Self {
+            mode: GameMode::default(),
+            stage,
+            spawned_piece: piece_state.piece.clone(),
+            piece_state,
+            held_piece: None,
+            can_hold: true,
+            piece_generator,
+            wait: Timings::default().gravity,
+            phase: Phase::Falling,
+            timings: Timings::default(),
+            match_size: DEFAULT_MATCH_SIZE,
+            cleared_line_indices: vec![],
+            chain: 0,
+            max_chain: 0,
+            score: 0,
+            cleared_lines: 0,
+            rise_wait: rising_floor::INITIAL_RISE_INTERVAL,
+            rise_interval: rising_floor::INITIAL_RISE_INTERVAL,
+            rng: StdRng::from_entropy(),
+            is_game_over: false,
+            stats: GameStats::default(),
+            piece_key_presses: 0,
+            counted_shift: None,
+            held_initial_actions: Default::default(),
+            history: History::default(),
+            subscribers: Subscribers::default(),
+        };
+        game.is_game_over = !game.can_put_piece_state(&game.piece_state);
+        game.stats.record_generated_piece(&game.piece_state.piece);
+        game.phase = game.piece_phase();
+        game.wait = game.phase_wait(game.phase);
+        game
+    }
+
+    fn with_piece_generator(stage: Stage, mut piece_generator: PieceGenerator) -> Self {
+        let piece_state = PieceState::with_initial_position(piece_generator.next(), stage.size());
+        Self::with_piece_state(stage, piece_state, piece_generator)
+    }
+
+    pub fn default_stage_size() -> BlockGridSize {
+        BlockGridSize::new(WIDTH, HEIGHT)
+    }
+
     pub fn new() -> Self {
-        let mut piece_generator = PieceGenerator::new(piece::standards());
-        let stage_size = BlockGridSize::new(WIDTH, HEIGHT);
+        Self::with_mode(GameMode::default())
+    }
+
+    pub fn with_mode(mode: GameMode) -> Self {
+        Self::with_piece_set(mode, PieceSet::standard())
+    }
+
+    pub fn with_piece_set(mode: GameMode, piece_set: PieceSet) -> Self {
         Self {
-            stage: Stage::new(stage_size),
-            piece_state: PieceState::with_initial_position(piece_generator.next(), stage_size),
-            piece_generator,
-            wait: WAIT,
+            mode,
+            ..Self::with_piece_generator(
+                Stage::new(Self::default_stage_size()),
+                PieceGenerator::new(piece_set.into_pieces()),
+            )
         }
     }
 
+    pub fn with_seed(piece_set: PieceSet, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ..Self::with_piece_generator(
+                Stage::new(Self::default_stage_size()),
+                PieceGenerator::with_seed(piece_set.into_pieces(), seed),
+            )
+        }
+    }
+
+    pub fn from_position(
+        piece_set: PieceSet,
+        stage: Stage,
+        piece_state: PieceState,
+        next_pieces: Vec<Piece>,
+        held_piece: Option<Piece>,
+        can_hold: bool,
+    ) -> Self {
+        Self {
+            held_piece,
+            can_hold,
+            ..Self::with_piece_state(
+                stage,
+                piece_state,
+                PieceGenerator::with_stocks(piece_set.into_pieces(), next_pieces),
+            )
+        }
+    }
+
+    pub fn with_timings(self, timings: Timings) -> Self {
+        let mut game = Self { timings, ..self };
+        game.wait = game.phase_wait(game.phase);
+        game
+    }
+
+    pub fn with_delayed_first_piece(mut self) -> Self {
+        self.piece_generator.put_back(self.spawned_piece.clone());
+        self.stats = GameStats::default();
+        self.phase = Phase::Spawning;
+        self.wait = self.phase_wait(self.phase);
+        self
+    }
+
+    pub fn with_match_size(self, match_size: num::NonZeroUsize) -> Self {
+        Self {
+            match_size: match_size.get(),
+            ..self
+        }
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn match_size(&self) -> usize {
+        self.match_size
+    }
+
     pub fn stage_size(&self) -> BlockGridSize {
         self.stage.size()
     }
 
-    fn can_transform_piece(
+    pub fn stage(&self) -> &Stage {
+        &self.stage
+    }
+
+    pub fn piece_state(&self) -> &PieceState {
+        &self.piece_state
+    }
+
+    pub fn held_piece(&self) -> Option<&Piece> {
+        self.held_piece.as_ref()
+    }
+
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
+    }
+
+    pub fn next_pieces(&mut self, count: usize) -> Vec<Piece> {
+        let mut pieces = self.piece_generator.peek(count);
+        pieces.truncate(count);
+        pieces
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn cleared_lines(&self) -> usize {
+        self.cleared_lines
+    }
+
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    pub fn chain(&self) -> usize {
+        self.chain
+    }
+
+    pub fn max_chain(&self) -> usize {
+        self.max_chain
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.is_game_over
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+
+    fn can_control_piece(&self) -> bool {
+        !self.is_game_over && self.phase.has_piece()
+    }
+
+    fn can_put_piece_state(&self, piece_state: &PieceState) -> bool {
+        piece_state
+            .blocks()
+            .all(|(index, _)| self.stage.can_put_to(index))
+    }
+
+    fn can_move_piece_state(
         &self,
-        mut transform: impl FnMut(BlockIndexOffset) -> BlockIndexOffset,
+        piece_state: &PieceState,
+        offset: euclid::TypedVector2D<isize, BlockSpace>,
     ) -> bool {
-        self.piece_state
+        piece_state
             .blocks()
-            .map(move |(index, _)| transform(index))
-            .all(|index| self.stage.can_put_to(index))
+            .all(|(index, _)| self.stage.can_put_to(index + offset))
     }
 
-    fn can_move_piece(&self, offset: euclid::TypedVector2D<isize, BlockSpace>) -> bool {
-        self.can_transform_piece(|index| index + offset)
+    fn moved_piece_state(
+        &self,
+        piece_state: &PieceState,
+        offset: euclid::TypedVector2D<isize, BlockSpace>,
+    ) -> Option<PieceState> {
+        if self.can_move_piece_state(piece_state, offset) {
+            Some(PieceState::new(
+                piece_state.piece.clone(),
+                piece_state.position + offset,
+            ))
+        } else {
+            None
+        }
     }
 
-    fn search_hard_drop_position(&self) -> BlockIndexOffset {
+    fn changed_piece_state(
+        &self,
+        piece_state: &PieceState,
+        new_piece: Piece,
+        kicks: &[(isize, isize)],
+    ) -> Option<PieceState> {
+        kicks
+            .iter()
+            .map(|&(x, y)| {
+                PieceState::new(
+                    new_piece.clone(),
+                    piece_state.position + euclid::TypedVector2D::new(x, y),
+                )
+            })
+            .find(|new_state| {
+                self.can_put_piece_state(new_state)
+                    && (new_state.position.y <= piece_state.position.y
+                        || new_state
+                            .blocks()
+                            .all(|(index, _)| self.stage.contains(index)))
+            })
+    }
+
+    fn hard_drop_position(&self, piece_state: &PieceState) -> BlockIndexOffset {
         let mut offset = euclid::TypedVector2D::new(0, 0);
-        while self.can_move_piece(offset + euclid::TypedVector2D::new(0, -1)) {
+        while self.can_move_piece_state(piece_state, offset + euclid::TypedVector2D::new(0, -1)) {
             offset.y -= 1;
         }
-        self.piece_state.position + offset
+        piece_state.position + offset
+    }
+
+    fn search_hard_drop_position(&self) -> BlockIndexOffset {
+        self.hard_drop_position(&self.piece_state)
     }
 
     fn change_piece_event(&self) -> GameEvent {
@@ -103,108 +371,500 @@ impl Game {
         }
     }
 
+    fn piece_phase(&self) -> Phase {
+        if self.can_move_piece_state(&self.piece_state, euclid::TypedVector2D::new(0, -1)) {
+            Phase::Falling
+        } else {
+            Phase::Locking
+        }
+    }
+
+    fn phase_wait(&self, phase: Phase) -> Frames {
+        match phase {
+            Phase::Falling => self.timings.gravity,
+            Phase::Locking => self.timings.lock,
+            Phase::Clearing => self.timings.line_clear,
+            Phase::Spawning => self.timings.entry,
+        }
+    }
+
+    fn change_phase(&mut self, phase: Phase) -> Vec<GameEvent> {
+        if self.phase == phase {
+            return vec![];
+        }
+        self.phase = phase;
+        self.wait = self.phase_wait(phase);
+        vec![GameEvent::ChangePhase(phase)]
+    }
+
+    fn update_piece_phase(&mut self) -> Vec<GameEvent> {
+        let phase = self.piece_phase();
+        self.change_phase(phase)
+    }
+
     fn piece_generation_events(&mut self) -> Vec<GameEvent> {
         vec![
             self.change_piece_event(),
             self.move_piece_event(),
-            GameEvent::UpdateNextPieces(self.piece_generator.peek(3)),
+            GameEvent::UpdateNextPieces(self.next_pieces(3)),
         ]
     }
 
     fn put_piece_blocks(&mut self) -> GameEvent {
         let mut put_results = vec![];
         for (index, block) in self.piece_state.blocks() {
-            if self.stage.can_put_to(index) {
+            if self.stage.contains(index) {
                 put_results.push(self.stage.put_block(index.cast::<usize>(), block));
             }
         }
         GameEvent::PutBlocks(put_results)
     }
 
-    fn remove_filled_lines(&mut self) -> Vec<GameEvent> {
-        if let Some((remove_results, move_results)) = self.stage.remove_filled_lines() {
-            vec![
-                GameEvent::RemoveBlocks(remove_results),
-                GameEvent::MoveBlocks(move_results),
-            ]
+    fn matched_groups(&self) -> Vec<Vec<BlockIndex>> {
+        if !self.mode.matches_colors() {
+            return vec![];
+        }
+        self.stage
+            .connected_groups(|block, other| block.number == other.number)
+            .into_iter()
+            .filter(|group| group.len() >= self.match_size)
+            .collect()
+    }
+
+    fn remove_cleared_blocks(&mut self) -> Vec<GameEvent> {
+        let line_indices = self.stage.filled_line_indices();
+        let matched_groups = self.matched_groups();
+        if line_indices.is_empty() && matched_groups.is_empty() {
+            return vec![];
+        }
+        self.chain += 1;
+        self.max_chain = self.max_chain.max(self.chain);
+        if !line_indices.is_empty() {
+            self.stats.record_line_clear(line_indices.len());
+        }
+        self.cleared_lines += line_indices.len();
+        let matched_block_count = matched_groups.iter().map(Vec::len).sum::<usize>() as u64;
+        self.score += (LINE_SCORES[line_indices.len().min(LINE_SCORES.len() - 1)]
+            + matched_block_count * MATCHED_BLOCK_SCORE)
+            * self.chain as u64;
+        let mut remove_results = self.stage.remove_lines(&line_indices);
+        for index in matched_groups.into_iter().flatten() {
+            remove_results.extend(self.stage.remove_block(index));
+        }
+        self.cleared_line_indices = line_indices;
+        vec![
+            GameEvent::Chain(self.chain),
+            GameEvent::RemoveBlocks(remove_results),
+        ]
+    }
+
+    fn collapse_cleared_blocks(&mut self) -> Vec<GameEvent> {
+        let line_indices = std::mem::take(&mut self.cleared_line_indices);
+        let move_results = if self.mode.has_sticky_gravity() {
+            self.stage.drop_groups()
+        } else if self.mode.matches_colors() {
+            self.stage.drop_blocks()
         } else {
+            self.stage.collapse_lines(&line_indices)
+        };
+        if move_results.is_empty() {
             vec![]
+        } else {
+            vec![GameEvent::MoveBlocks(move_results)]
+        }
+    }
+
+    fn spawn_piece(&mut self, piece: Piece) -> Vec<GameEvent> {
+        self.piece_state = PieceState::with_initial_position(piece.clone(), self.stage_size());
+        self.spawned_piece = piece;
+        self.wait = self.timings.gravity;
+        self.piece_key_presses = 0;
+        self.counted_shift = None;
+        let mut events = self.piece_generation_events();
+        events.append(&mut self.update_piece_phase());
+        if !self.can_put_piece_state(&self.piece_state) {
+            self.is_game_over = true;
+            events.push(GameEvent::GameOver);
+        }
+        events
+    }
+
+    fn next_piece(&mut self) -> Piece {
+        let piece = self.piece_generator.next();
+        self.stats.record_generated_piece(&piece);
+        piece
+    }
+
+    fn spawn_next_piece(&mut self) -> Vec<GameEvent> {
+        self.can_hold = true;
+        let piece = self.next_piece();
+        let mut events = self.spawn_piece(piece);
+        events.append(&mut self.apply_initial_actions());
+        events
+    }
+
+    fn start_delay(&mut self, phase: Phase) -> Vec<GameEvent> {
+        let mut events = self.change_phase(phase);
+        if self.wait == 0 {
+            events.append(&mut self.finish_delay());
+        }
+        events
+    }
+
+    fn finish_delay(&mut self) -> Vec<GameEvent> {
+        match self.phase {
+            Phase::Clearing => {
+                let mut events = self.collapse_cleared_blocks();
+                let mut remove_events = self.remove_cleared_blocks();
+                if remove_events.is_empty() {
+                    events.append(&mut self.start_delay(Phase::Spawning));
+                } else {
+                    events.append(&mut remove_events);
+                    self.wait = self.phase_wait(Phase::Clearing);
+                    if self.wait == 0 {
+                        events.append(&mut self.finish_delay());
+                    }
+                }
+                events
+            }
+            Phase::Spawning => self.spawn_next_piece(),
+            Phase::Falling | Phase::Locking => vec![],
         }
     }
 
     fn fix_piece(&mut self) -> Vec<GameEvent> {
-        let mut events = vec![GameEvent::RemovePiece];
+        let mut events = if self.mode.checks_finesse() {
+            self.check_finesse()
+        } else {
+            vec![]
+        };
+        if !events.is_empty() && self.mode.restarts_on_finesse_fault() {
+            events.append(&mut self.restart_piece());
+            return events;
+        }
+        if self.mode.records_history() {
+            self.record_history();
+        }
+        let is_locked_out = self
+            .piece_state
+            .blocks()
+            .any(|(index, _)| !self.stage.contains(index));
+        self.chain = 0;
+        events.push(GameEvent::RemovePiece);
         events.push(self.put_piece_blocks());
-        events.append(&mut self.remove_filled_lines());
-        self.piece_state =
-            PieceState::with_initial_position(self.piece_generator.next(), self.stage_size());
-        events.append(&mut self.piece_generation_events());
+        let mut remove_events = self.remove_cleared_blocks();
+        let has_cleared_blocks = !remove_events.is_empty();
+        self.stats.record_lock(has_cleared_blocks);
+        events.append(&mut remove_events);
+        if is_locked_out {
+            if has_cleared_blocks {
+                events.append(&mut self.collapse_cleared_blocks());
+            }
+            self.is_game_over = true;
+            events.push(GameEvent::GameOver);
+            return events;
+        }
+        if has_cleared_blocks {
+            events.append(&mut self.start_delay(Phase::Clearing));
+        } else {
+            events.append(&mut self.start_delay(Phase::Spawning));
+        }
         events
     }
 
     pub fn initial_events(&mut self) -> Vec<GameEvent> {
-        self.piece_generation_events()
+        let events = if self.phase.has_piece() {
+            self.piece_generation_events()
+        } else {
+            vec![GameEvent::UpdateNextPieces(self.next_pieces(3))]
+        };
+        self.publish(events)
     }
 
-    pub fn update(&mut self, delta: f64) -> Vec<GameEvent> {
-        if self.wait <= delta {
-            self.wait = WAIT - (delta - self.wait);
-            self.drop_piece_soft()
+    pub fn tick(&mut self) -> Vec<GameEvent> {
+        let events = self.advance_frame();
+        self.publish(events)
+    }
+
+    fn advance_frame(&mut self) -> Vec<GameEvent> {
+        if self.is_game_over {
+            return vec![];
+        }
+        self.stats.frames += 1;
+        let mut events = if self.mode.raises_floor() {
+            self.tick_floor()
         } else {
-            self.wait -= delta;
             vec![]
+        };
+        self.wait = self.wait.saturating_sub(1);
+        if self.is_game_over || self.wait > 0 {
+            return events;
         }
+        events.append(&mut match self.phase {
+            Phase::Falling => {
+                self.wait = self.timings.gravity;
+                self.drop_piece()
+            }
+            Phase::Locking => self.fix_piece(),
+            Phase::Clearing | Phase::Spawning => self.finish_delay(),
+        });
+        events
+    }
+
+    fn count_key_press(&mut self) {
+        self.stats.key_presses += 1;
+        self.piece_key_presses += 1;
     }
 
     fn try_move_piece(&mut self, offset: isize) -> Vec<GameEvent> {
-        if self.can_move_piece(euclid::TypedVector2D::new(offset, 0)) {
-            self.piece_state.position.x += offset;
-            vec![self.move_piece_event()]
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        self.count_key_press();
+        self.counted_shift = Some(offset);
+        self.shift_piece(euclid::TypedVector2D::new(offset, 0))
+    }
+
+    fn shift_piece(&mut self, offset: euclid::TypedVector2D<isize, BlockSpace>) -> Vec<GameEvent> {
+        if let Some(new_state) = self.moved_piece_state(&self.piece_state, offset) {
+            self.piece_state = new_state;
+            let mut events = vec![self.move_piece_event()];
+            events.append(&mut self.update_piece_phase());
+            events
         } else {
             vec![]
         }
     }
 
     pub fn move_piece_left(&mut self) -> Vec<GameEvent> {
-        self.try_move_piece(-1)
+        let events = self.try_move_piece(-1);
+        self.publish(events)
     }
 
     pub fn move_piece_right(&mut self) -> Vec<GameEvent> {
-        self.try_move_piece(1)
+        let events = self.try_move_piece(1);
+        self.publish(events)
     }
 
-    pub fn drop_piece_soft(&mut self) -> Vec<GameEvent> {
-        if self.can_move_piece(euclid::TypedVector2D::new(0, -1)) {
-            self.piece_state.position.y -= 1;
-            vec![self.move_piece_event()]
-        } else {
+    fn try_repeat_piece_move(&mut self, offset: isize) -> Vec<GameEvent> {
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        if self.counted_shift != Some(offset) {
+            self.count_key_press();
+            self.counted_shift = Some(offset);
+        }
+        self.shift_piece(euclid::TypedVector2D::new(offset, 0))
+    }
+
+    pub fn repeat_move_piece_left(&mut self) -> Vec<GameEvent> {
+        let events = self.try_repeat_piece_move(-1);
+        self.publish(events)
+    }
+
+    pub fn repeat_move_piece_right(&mut self) -> Vec<GameEvent> {
+        let events = self.try_repeat_piece_move(1);
+        self.publish(events)
+    }
+
+    pub fn repeat_drop_piece_soft(&mut self) -> Vec<GameEvent> {
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        let events = self.shift_piece(euclid::TypedVector2D::new(0, -1));
+        self.publish(events)
+    }
+
+    fn drop_piece(&mut self) -> Vec<GameEvent> {
+        let events = self.shift_piece(euclid::TypedVector2D::new(0, -1));
+        if events.is_empty() {
             self.fix_piece()
+        } else {
+            events
         }
     }
 
-    pub fn drop_piece_hard(&mut self) -> Vec<GameEvent> {
+    fn try_drop_piece_soft(&mut self) -> Vec<GameEvent> {
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        self.count_key_press();
+        self.drop_piece()
+    }
+
+    pub fn drop_piece_soft(&mut self) -> Vec<GameEvent> {
+        let events = self.try_drop_piece_soft();
+        self.publish(events)
+    }
+
+    fn try_drop_piece_hard(&mut self) -> Vec<GameEvent> {
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        self.stats.key_presses += 1;
         self.piece_state.position = self.search_hard_drop_position();
         self.fix_piece()
     }
 
-    fn try_change_piece(&mut self, new_piece: Piece) -> Vec<GameEvent> {
-        let new_state = PieceState::new(new_piece, self.piece_state.position);
-        if new_state
-            .blocks()
-            .all(|(index, _)| self.stage.can_put_to(index))
-        {
+    pub fn drop_piece_hard(&mut self) -> Vec<GameEvent> {
+        let events = self.try_drop_piece_hard();
+        self.publish(events)
+    }
+
+    fn try_change_piece(&mut self, new_piece: Piece, kicks: &[(isize, isize)]) -> Vec<GameEvent> {
+        if !self.can_control_piece() {
+            return vec![];
+        }
+        self.count_key_press();
+        self.change_piece(new_piece, kicks)
+    }
+
+    fn change_piece(&mut self, new_piece: Piece, kicks: &[(isize, isize)]) -> Vec<GameEvent> {
+        if let Some(new_state) = self.changed_piece_state(&self.piece_state, new_piece, kicks) {
             self.piece_state = new_state;
-            vec![self.change_piece_event()]
+            let mut events = vec![self.change_piece_event()];
+            events.append(&mut self.update_piece_phase());
+            events
         } else {
             vec![]
         }
     }
 
+    fn try_rotate_piece_right(&mut self) -> Vec<GameEvent> {
+        self.try_change_piece(self.piece_state.piece.rotate_right(), &NO_KICKS)
+    }
+
+    fn try_rotate_piece_left(&mut self) -> Vec<GameEvent> {
+        self.try_change_piece(self.piece_state.piece.rotate_left(), &NO_KICKS)
+    }
+
+    fn try_rotate_piece_180(&mut self) -> Vec<GameEvent> {
+        self.try_change_piece(self.piece_state.piece.rotate_180(), &ROTATION_180_KICKS)
+    }
+
     pub fn rotate_piece_right(&mut self) -> Vec<GameEvent> {
-        self.try_change_piece(self.piece_state.piece.rotate_right())
+        let events = self.try_rotate_piece_right();
+        self.publish(events)
     }
 
     pub fn rotate_piece_left(&mut self) -> Vec<GameEvent> {
-        self.try_change_piece(self.piece_state.piece.rotate_left())
+        let events = self.try_rotate_piece_left();
+        self.publish(events)
+    }
+
+    pub fn rotate_piece_180(&mut self) -> Vec<GameEvent> {
+        let events = self.try_rotate_piece_180();
+        self.publish(events)
+    }
+
+    pub fn hold_piece(&mut self) -> Vec<GameEvent> {
+        let events = self.try_hold_piece();
+        self.publish(events)
+    }
+
+    fn try_hold_piece(&mut self) -> Vec<GameEvent> {
+        if !self.can_control_piece() || !self.can_hold {
+            return vec![];
+        }
+        self.count_key_press();
+        self.swap_held_piece()
+    }
+
+    fn swap_held_piece(&mut self) -> Vec<GameEvent> {
+        self.can_hold = false;
+        let piece = self
+            .held_piece
+            .replace(self.spawned_piece.clone())
+            .unwrap_or_else(|| self.next_piece());
+        let mut events = vec![GameEvent::UpdateHoldPiece(self.held_piece.clone())];
+        events.append(&mut self.spawn_piece(piece));
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chains(events: Vec<GameEvent>) -> Vec<usize> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                GameEvent::Chain(chain) => Some(chain),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn cascade_game() -> Game {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in 0..9 {
+            stage.put_block(BlockIndex::new(x, 0), Block::new(1));
+        }
+        for x in 4..10 {
+            stage.put_block(BlockIndex::new(x, 1), Block::new(2));
+        }
+        stage.put_block(BlockIndex::new(9, 2), Block::new(3));
+        let pieces = piece::standards();
+        let piece_state = PieceState::with_initial_position(pieces[0].clone(), stage.size());
+        let mut game =
+            Game::from_position(PieceSet::standard(), stage, piece_state, pieces, None, true);
+        game.mode = GameMode::Cascade;
+        game
+    }
+
+    #[test]
+    fn chains_line_clears_through_sticky_gravity() {
+        let mut game = cascade_game();
+        for _ in 0..3 {
+            game.move_piece_left();
+        }
+        assert_eq!(chains(game.drop_piece_hard()), vec![1, 2]);
+        assert_eq!(game.max_chain(), 2);
+        assert_eq!(game.cleared_lines(), 2);
+        assert_eq!(game.score(), LINE_SCORES[1] + LINE_SCORES[1] * 2);
+        assert_eq!(game.stage().blocks().count(), 0);
+    }
+
+    fn color_match_game(stage: Stage, match_size: usize) -> Game {
+        let pieces = piece::standards();
+        let piece_state = PieceState::with_initial_position(pieces[0].clone(), stage.size());
+        let mut game =
+            Game::from_position(PieceSet::standard(), stage, piece_state, pieces, None, true)
+                .with_match_size(num::NonZeroUsize::new(match_size).unwrap());
+        game.mode = GameMode::ColorMatch;
+        game
+    }
+
+    #[test]
+    fn clears_groups_from_the_match_size() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        stage.put_block(BlockIndex::new(7, 0), Block::new(0));
+        let mut game = color_match_game(stage.clone(), 6);
+        assert!(chains(game.drop_piece_hard()).is_empty());
+        assert_eq!(game.stage().blocks().count(), 5);
+
+        let mut game = color_match_game(stage, 5);
+        assert_eq!(chains(game.drop_piece_hard()), vec![1]);
+        assert_eq!(game.stage().blocks().count(), 0);
+        assert_eq!(game.score(), 5 * MATCHED_BLOCK_SCORE);
+    }
+
+    #[test]
+    fn chains_matches_through_dropped_blocks() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in 0..3 {
+            stage.put_block(BlockIndex::new(x, 0), Block::new(1));
+        }
+        stage.put_block(BlockIndex::new(3, 0), Block::new(0));
+        stage.put_block(BlockIndex::new(3, 1), Block::new(1));
+        let mut game = color_match_game(stage, 4);
+        game.move_piece_right();
+        assert_eq!(chains(game.drop_piece_hard()), vec![1, 2]);
+        assert_eq!(game.stage().blocks().count(), 0);
+        assert_eq!(
+            game.score(),
+            5 * MATCHED_BLOCK_SCORE + 4 * MATCHED_BLOCK_SCORE * 2
+        );
     }
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 pub type BlockNumber = u32;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub const GARBAGE_BLOCK_NUMBER: BlockNumber = 7;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Block {
     pub number: BlockNumber,
 }
@@ -20,4 +24,37 @@ pub type BlockGridSize = array2d::Size<BlockSpace>;
 
 pub type BlockIndex = array2d::Index<BlockSpace>;
 
-pub type BlockIndexOffset = euclid::TypedPoint2D<isize, BlockSpace>;
\ No newline at end of file
+pub type BlockIndexOffset = euclid::TypedPoint2D<isize, BlockSpace>;
+
+pub(super) mod index_serde {
+    use super::BlockIndex;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(index: &BlockIndex, serializer: S) -> Result<S::Ok, S::Error> {
+        [index.x, index.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BlockIndex, D::Error> {
+        let [x, y] = <[usize; 2]>::deserialize(deserializer)?;
+        Ok(BlockIndex::new(x, y))
+    }
+}
+
+pub(super) mod offset_serde {
+    use super::BlockIndexOffset;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        offset: &BlockIndexOffset,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        [offset.x, offset.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BlockIndexOffset, D::Error> {
+        let [x, y] = <[isize; 2]>::deserialize(deserializer)?;
+        Ok(BlockIndexOffset::new(x, y))
+    }
+}
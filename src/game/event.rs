@@ -1,31 +1,55 @@
-use super::block::{Block, BlockIndex, BlockIndexOffset};
+use super::block::{index_serde, offset_serde, Block, BlockIndex, BlockIndexOffset};
+use super::phase::Phase;
 use super::piece::Piece;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PutResult {
     pub block: Block,
+    #[serde(with = "index_serde")]
     pub index: BlockIndex
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RemoveResult {
     pub block: Block,
+    #[serde(with = "index_serde")]
     pub index: BlockIndex
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MoveResult {
     pub block: Block,
+    #[serde(with = "index_serde")]
     pub source: BlockIndex,
+    #[serde(with = "index_serde")]
     pub destination: BlockIndex
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum GameEvent {
-    ChangePiece {piece: Piece, guide_position: BlockIndexOffset},
-    MovePiece {piece: Piece, position: BlockIndexOffset, guide_position: BlockIndexOffset},
+    ChangePiece {
+        piece: Piece,
+        #[serde(with = "offset_serde")]
+        guide_position: BlockIndexOffset,
+    },
+    MovePiece {
+        piece: Piece,
+        #[serde(with = "offset_serde")]
+        position: BlockIndexOffset,
+        #[serde(with = "offset_serde")]
+        guide_position: BlockIndexOffset,
+    },
     RemovePiece,
     UpdateNextPieces(Vec<Piece>),
+    UpdateHoldPiece(Option<Piece>),
     PutBlocks(Vec<PutResult>),
+    RestoreBlocks(Vec<PutResult>),
     RemoveBlocks(Vec<RemoveResult>),
     MoveBlocks(Vec<MoveResult>),
+    ChangePhase(Phase),
+    Chain(usize),
+    FinesseFault { expected: usize, actual: usize },
+    GameOver,
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use super::event::GameEvent;
+use super::placement::{PieceInput, MOVING_INPUTS};
+use super::{Game, PieceState};
+use std::collections;
+
+impl Game {
+    fn piece_state_shifted_to_wall(
+        &self,
+        piece_state: &PieceState,
+        offset: isize,
+    ) -> Option<PieceState> {
+        let offset = euclid::TypedVector2D::new(offset, 0);
+        let mut shifted = self.moved_piece_state(piece_state, offset)?;
+        while let Some(next) = self.moved_piece_state(&shifted, offset) {
+            shifted = next;
+        }
+        Some(shifted)
+    }
+
+    fn next_finesse_states(&self, piece_state: &PieceState) -> Vec<PieceState> {
+        let mut next_states = MOVING_INPUTS
+            .iter()
+            .filter_map(|&input| self.piece_state_after_input(piece_state, input))
+            .collect::<Vec<_>>();
+        next_states.extend(self.piece_state_shifted_to_wall(piece_state, -1));
+        next_states.extend(self.piece_state_shifted_to_wall(piece_state, 1));
+        next_states
+    }
+
+    // Charging DAS into a wall takes a single key press, so the search treats it as one input.
+    fn minimum_piece_inputs(&self) -> Option<usize> {
+        let spawn_state =
+            PieceState::with_initial_position(self.spawned_piece.clone(), self.stage_size());
+        if !self.can_put_piece_state(&spawn_state) {
+            return None;
+        }
+        let cells = self
+            .piece_state
+            .blocks()
+            .map(|(index, _)| index)
+            .collect::<collections::HashSet<_>>();
+        let mut input_counts = collections::HashMap::new();
+        let mut queue = collections::VecDeque::new();
+        input_counts.insert(spawn_state.clone(), 0);
+        queue.push_back(spawn_state);
+        while let Some(piece_state) = queue.pop_front() {
+            let input_count = input_counts[&piece_state];
+            let landing = self
+                .piece_state_after_input(&piece_state, PieceInput::HardDrop)
+                .unwrap();
+            if landing.blocks().all(|(index, _)| cells.contains(&index)) {
+                return Some(input_count);
+            }
+            for next in self.next_finesse_states(&piece_state) {
+                if !input_counts.contains_key(&next) {
+                    input_counts.insert(next.clone(), input_count + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    pub(super) fn check_finesse(&mut self) -> Vec<GameEvent> {
+        let expected = match self.minimum_piece_inputs() {
+            Some(expected) => expected,
+            None => return vec![],
+        };
+        let actual = self.piece_key_presses;
+        if actual <= expected {
+            return vec![];
+        }
+        self.stats.finesse_faults += 1;
+        vec![GameEvent::FinesseFault { expected, actual }]
+    }
+
+    pub(super) fn restart_piece(&mut self) -> Vec<GameEvent> {
+        self.piece_state =
+            PieceState::with_initial_position(self.spawned_piece.clone(), self.stage_size());
+        self.piece_key_presses = 0;
+        self.counted_shift = None;
+        self.wait = self.timings.gravity;
+        let mut events = vec![self.change_piece_event(), self.move_piece_event()];
+        events.append(&mut self.update_piece_phase());
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{piece, BlockGridSize, GameMode, PieceSet, Stage};
+
+    fn finesse_game() -> Game {
+        let stage = Stage::new(BlockGridSize::new(10, 20));
+        let pieces = piece::standards();
+        let piece_state = PieceState::with_initial_position(pieces[0].clone(), stage.size());
+        let mut game =
+            Game::from_position(PieceSet::standard(), stage, piece_state, pieces, None, true);
+        game.mode = GameMode::Finesse;
+        game
+    }
+
+    fn finesse_faults(events: &[GameEvent]) -> Vec<(usize, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                GameEvent::FinesseFault { expected, actual } => Some((expected, actual)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accepts_minimal_inputs() {
+        let mut game = finesse_game();
+        game.move_piece_left();
+        game.move_piece_left();
+        assert!(finesse_faults(&game.drop_piece_hard()).is_empty());
+        assert_eq!(game.stats().finesse_faults, 0);
+    }
+
+    #[test]
+    fn flags_wasted_rotations() {
+        let mut game = finesse_game();
+        game.rotate_piece_right();
+        game.rotate_piece_left();
+        assert_eq!(finesse_faults(&game.drop_piece_hard()), vec![(0, 2)]);
+        assert_eq!(game.stats().finesse_faults, 1);
+    }
+
+    #[test]
+    fn restarts_the_piece_in_strict_mode() {
+        let mut game = finesse_game();
+        game.mode = GameMode::StrictFinesse;
+        let spawn_state = game.piece_state().clone();
+        game.rotate_piece_right();
+        game.rotate_piece_left();
+        assert_eq!(finesse_faults(&game.drop_piece_hard()), vec![(0, 2)]);
+        assert_eq!(game.piece_state(), &spawn_state);
+        assert!((0..20).all(|y| game.stage().row_mask(y) == 0));
+        assert_eq!(game.piece_key_presses, 0);
+    }
+
+    #[test]
+    fn flags_tapping_into_a_wall() {
+        let mut game = finesse_game();
+        for _ in 0..3 {
+            game.move_piece_left();
+        }
+        assert_eq!(finesse_faults(&game.drop_piece_hard()), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn accepts_charging_into_a_wall() {
+        let mut game = finesse_game();
+        game.move_piece_left();
+        game.repeat_move_piece_left();
+        game.repeat_move_piece_left();
+        assert!(finesse_faults(&game.drop_piece_hard()).is_empty());
+        assert_eq!(game.stats().key_presses, 2);
+    }
+
+    #[test]
+    fn counts_a_charge_held_from_the_previous_piece() {
+        let mut game = finesse_game();
+        for _ in 0..3 {
+            game.repeat_move_piece_left();
+        }
+        assert_eq!(game.piece_key_presses, 1);
+        game.repeat_move_piece_right();
+        assert_eq!(game.piece_key_presses, 2);
+    }
+}
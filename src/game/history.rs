@@ -0,0 +1,125 @@
+use super::event::{GameEvent, PutResult};
+use super::{Game, PieceState};
+use std::mem;
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo_stack: Vec<Game>,
+    redo_stack: Vec<Game>,
+}
+
+impl Game {
+    fn snapshot(&mut self) -> Game {
+        let history = mem::take(&mut self.history);
+        let mut snapshot = self.clone();
+        self.history = history;
+        snapshot.piece_state =
+            PieceState::with_initial_position(snapshot.spawned_piece.clone(), self.stage_size());
+        snapshot.phase = snapshot.piece_phase();
+        snapshot.wait = snapshot.phase_wait(snapshot.phase);
+        snapshot
+    }
+
+    pub(super) fn record_history(&mut self) {
+        let snapshot = self.snapshot();
+        self.history.undo_stack.push(snapshot);
+        self.history.redo_stack.clear();
+    }
+
+    fn restore(&mut self, snapshot: Game) -> Vec<GameEvent> {
+        let history = mem::take(&mut self.history);
+        let held_initial_actions = mem::take(&mut self.held_initial_actions);
+        let subscribers = mem::take(&mut self.subscribers);
+        *self = snapshot;
+        self.history = history;
+        self.held_initial_actions = held_initial_actions;
+        self.subscribers = subscribers;
+        let mut events = vec![
+            GameEvent::RemovePiece,
+            GameEvent::RestoreBlocks(
+                self.stage
+                    .blocks()
+                    .map(|(index, block)| PutResult { block, index })
+                    .collect(),
+            ),
+            GameEvent::UpdateHoldPiece(self.held_piece.clone()),
+        ];
+        events.append(&mut self.piece_generation_events());
+        events
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo_stack.is_empty()
+    }
+
+    fn try_undo(&mut self) -> Vec<GameEvent> {
+        if !self.phase.has_piece() {
+            return vec![];
+        }
+        if let Some(snapshot) = self.history.undo_stack.pop() {
+            let current = self.snapshot();
+            self.history.redo_stack.push(current);
+            self.restore(snapshot)
+        } else {
+            vec![]
+        }
+    }
+
+    fn try_redo(&mut self) -> Vec<GameEvent> {
+        if !self.phase.has_piece() {
+            return vec![];
+        }
+        if let Some(snapshot) = self.history.redo_stack.pop() {
+            let current = self.snapshot();
+            self.history.undo_stack.push(current);
+            self.restore(snapshot)
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn undo(&mut self) -> Vec<GameEvent> {
+        let events = self.try_undo();
+        self.publish(events)
+    }
+
+    pub fn redo(&mut self) -> Vec<GameEvent> {
+        let events = self.try_redo();
+        self.publish(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameMode;
+
+    #[test]
+    fn undoes_and_redoes_placements() {
+        let mut game = Game::with_mode(GameMode::Practice);
+        let spawned_state = game.piece_state().clone();
+        game.drop_piece_hard();
+        let blocks = game.stage().blocks().collect::<Vec<_>>();
+        let next_state = game.piece_state().clone();
+        let next_pieces = game.next_pieces(5);
+        assert!(game.can_undo());
+        assert!(!game.can_redo());
+
+        game.undo();
+        assert_eq!(game.stage().blocks().count(), 0);
+        assert_eq!(game.piece_state(), &spawned_state);
+        assert!(!game.can_undo());
+        assert!(game.can_redo());
+
+        game.redo();
+        assert_eq!(game.stage().blocks().collect::<Vec<_>>(), blocks);
+        assert_eq!(game.piece_state(), &next_state);
+        assert_eq!(game.next_pieces(5), next_pieces);
+        assert!(game.can_undo());
+        assert!(!game.can_redo());
+    }
+}
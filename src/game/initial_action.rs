@@ -0,0 +1,91 @@
+use super::event::GameEvent;
+use super::{Game, NO_KICKS, ROTATION_180_KICKS};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InitialAction {
+    RotateLeft,
+    RotateRight,
+    Rotate180,
+    Hold,
+}
+
+impl Game {
+    pub fn set_initial_action_held(&mut self, action: InitialAction, is_held: bool) {
+        if is_held {
+            self.held_initial_actions.insert(action);
+        } else {
+            self.held_initial_actions.remove(&action);
+        }
+    }
+
+    pub fn is_initial_action_held(&self, action: InitialAction) -> bool {
+        self.held_initial_actions.contains(&action)
+    }
+
+    pub(super) fn apply_initial_actions(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        if self.is_initial_action_held(InitialAction::Hold) && self.can_control_piece() {
+            events.append(&mut self.swap_held_piece());
+        }
+        if !self.can_control_piece() {
+            return events;
+        }
+        let piece = &self.piece_state.piece;
+        if self.is_initial_action_held(InitialAction::Rotate180) {
+            events.append(&mut self.change_piece(piece.rotate_180(), &ROTATION_180_KICKS));
+        } else if self.is_initial_action_held(InitialAction::RotateRight) {
+            events.append(&mut self.change_piece(piece.rotate_right(), &NO_KICKS));
+        } else if self.is_initial_action_held(InitialAction::RotateLeft) {
+            events.append(&mut self.change_piece(piece.rotate_left(), &NO_KICKS));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Phase, Timings};
+
+    #[test]
+    fn rotates_spawned_pieces_without_counting_key_presses() {
+        let mut game = Game::new();
+        game.set_initial_action_held(InitialAction::RotateRight, true);
+        game.drop_piece_hard();
+        assert_eq!(game.piece_state().piece, game.spawned_piece.rotate_right());
+        assert_eq!(game.stats().key_presses, 1);
+        assert_eq!(game.piece_key_presses, 0);
+    }
+
+    #[test]
+    fn holds_spawned_pieces_without_counting_key_presses() {
+        let mut game = Game::new();
+        let next_piece = game.next_pieces(1).remove(0);
+        game.set_initial_action_held(InitialAction::Hold, true);
+        game.drop_piece_hard();
+        assert_eq!(game.held_piece(), Some(&next_piece));
+        assert!(!game.can_hold());
+        assert_eq!(game.stats().key_presses, 1);
+        assert_eq!(game.piece_key_presses, 0);
+    }
+
+    #[test]
+    fn rotates_the_first_piece_after_the_entry_delay() {
+        let mut game = Game::new()
+            .with_timings(Timings {
+                entry: 2,
+                ..Timings::default()
+            })
+            .with_delayed_first_piece();
+        let first_piece = game.next_pieces(1).remove(0);
+        assert_eq!(game.phase(), Phase::Spawning);
+        game.set_initial_action_held(InitialAction::RotateLeft, true);
+        game.tick();
+        assert_eq!(game.phase(), Phase::Spawning);
+        game.tick();
+        assert_eq!(game.phase(), Phase::Falling);
+        assert_eq!(game.piece_state().piece, first_piece.rotate_left());
+        assert_eq!(game.stats().key_presses, 0);
+        assert_eq!(game.stats().piece_counts.values().sum::<usize>(), 1);
+    }
+}
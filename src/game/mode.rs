@@ -0,0 +1,67 @@
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    Practice,
+    Cascade,
+    ColorMatch,
+    Invisible,
+    Survival,
+    Finesse,
+    StrictFinesse,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 8] = [
+        GameMode::Normal,
+        GameMode::Practice,
+        GameMode::Cascade,
+        GameMode::ColorMatch,
+        GameMode::Invisible,
+        GameMode::Survival,
+        GameMode::Finesse,
+        GameMode::StrictFinesse,
+    ];
+
+    pub fn name(self) -> &'static str {
+        use GameMode::*;
+        match self {
+            Normal => "normal",
+            Practice => "practice",
+            Cascade => "cascade",
+            ColorMatch => "color-match",
+            Invisible => "invisible",
+            Survival => "survival",
+            Finesse => "finesse",
+            StrictFinesse => "strict-finesse",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    pub fn records_history(self) -> bool {
+        self == GameMode::Practice
+    }
+
+    pub fn has_sticky_gravity(self) -> bool {
+        self == GameMode::Cascade
+    }
+
+    pub fn raises_floor(self) -> bool {
+        self == GameMode::Survival
+    }
+
+    pub fn checks_finesse(self) -> bool {
+        matches!(self, GameMode::Finesse | GameMode::StrictFinesse)
+    }
+
+    pub fn restarts_on_finesse_fault(self) -> bool {
+        self == GameMode::StrictFinesse
+    }
+
+    pub fn matches_colors(self) -> bool {
+        self == GameMode::ColorMatch
+    }
+}
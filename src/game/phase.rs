@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Falling,
+    Locking,
+    Clearing,
+    Spawning,
+}
+
+impl Phase {
+    pub fn has_piece(self) -> bool {
+        match self {
+            Phase::Falling | Phase::Locking => true,
+            Phase::Clearing | Phase::Spawning => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        piece, Block, BlockGridSize, BlockIndex, Game, GameEvent, PieceSet, PieceState, Stage,
+        Timings,
+    };
+
+    fn game_with_stage(stage: Stage) -> Game {
+        let pieces = piece::standards();
+        let piece_state = PieceState::with_initial_position(pieces[0].clone(), stage.size());
+        Game::from_position(PieceSet::standard(), stage, piece_state, pieces, None, true)
+            .with_timings(Timings {
+                gravity: 1,
+                lock: 2,
+                line_clear: 3,
+                entry: 4,
+            })
+    }
+
+    fn phase_changes(game: &mut Game, frames: usize) -> Vec<(usize, Phase)> {
+        (1..=frames)
+            .flat_map(|frame| {
+                game.tick()
+                    .into_iter()
+                    .filter_map(move |event| match event {
+                        GameEvent::ChangePhase(phase) => Some((frame, phase)),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn waits_for_lock_and_entry_delays() {
+        let mut game = game_with_stage(Stage::new(BlockGridSize::new(10, 20)));
+        let changes = phase_changes(&mut game, 40);
+        let phases = changes.iter().map(|&(_, phase)| phase).collect::<Vec<_>>();
+        assert_eq!(
+            phases,
+            vec![Phase::Locking, Phase::Spawning, Phase::Falling]
+        );
+        let frames = changes.iter().map(|&(frame, _)| frame).collect::<Vec<_>>();
+        assert_eq!(frames[1] - frames[0], 2);
+        assert_eq!(frames[2] - frames[1], 4);
+    }
+
+    #[test]
+    fn waits_for_line_clear_delay_before_collapsing_lines() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in (0..3).chain(7..10) {
+            stage.put_block(BlockIndex::new(x, 0), Block::new(1));
+        }
+        let mut game = game_with_stage(stage);
+        let changes = phase_changes(&mut game, 40);
+        let phases = changes.iter().map(|&(_, phase)| phase).collect::<Vec<_>>();
+        assert_eq!(
+            phases,
+            vec![
+                Phase::Locking,
+                Phase::Clearing,
+                Phase::Spawning,
+                Phase::Falling
+            ]
+        );
+        let frames = changes.iter().map(|&(frame, _)| frame).collect::<Vec<_>>();
+        assert_eq!(frames[1] - frames[0], 2);
+        assert_eq!(frames[2] - frames[1], 3);
+        assert_eq!(frames[3] - frames[2], 4);
+        assert_eq!(game.stage().row_mask(0), 0);
+        assert_eq!(game.cleared_lines(), 1);
+    }
+}
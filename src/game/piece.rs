@@ -1,42 +1,132 @@
-use super::block::{Block, BlockGridSize, BlockIndex};
+use super::block::{Block, BlockGridSize, BlockIndex, BlockSpace};
+use serde::{Deserialize, Serialize};
 use std::iter;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(from = "SerializedPiece", into = "SerializedPiece")]
 pub struct Piece {
     size: BlockGridSize,
     block: Block,
     indices: Vec<BlockIndex>,
+    spawn_offset: euclid::TypedVector2D<isize, BlockSpace>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedPiece {
+    size: [usize; 2],
+    block: Block,
+    cells: Vec<[usize; 2]>,
+    spawn_offset: [isize; 2],
+}
+
+impl From<Piece> for SerializedPiece {
+    fn from(piece: Piece) -> Self {
+        Self {
+            size: [piece.size.width, piece.size.height],
+            block: piece.block,
+            cells: piece
+                .indices
+                .iter()
+                .map(|index| [index.x, index.y])
+                .collect(),
+            spawn_offset: [piece.spawn_offset.x, piece.spawn_offset.y],
+        }
+    }
+}
+
+impl From<SerializedPiece> for Piece {
+    fn from(message: SerializedPiece) -> Self {
+        Piece::new(
+            BlockGridSize::new(message.size[0], message.size[1]),
+            message.block,
+            message
+                .cells
+                .iter()
+                .map(|&[x, y]| BlockIndex::new(x, y))
+                .collect(),
+        )
+        .with_spawn_offset(euclid::TypedVector2D::new(
+            message.spawn_offset[0],
+            message.spawn_offset[1],
+        ))
+    }
 }
 
 impl Piece {
+    pub fn new(size: BlockGridSize, block: Block, indices: Vec<BlockIndex>) -> Self {
+        Self {
+            size,
+            block,
+            indices,
+            spawn_offset: euclid::TypedVector2D::new(0, 0),
+        }
+    }
+
+    pub fn with_spawn_offset(self, spawn_offset: euclid::TypedVector2D<isize, BlockSpace>) -> Self {
+        Self {
+            spawn_offset,
+            ..self
+        }
+    }
+
     pub fn size(&self) -> BlockGridSize {
         self.size
     }
 
+    pub fn spawn_offset(&self) -> euclid::TypedVector2D<isize, BlockSpace> {
+        self.spawn_offset
+    }
+
+    pub fn block(&self) -> Block {
+        self.block
+    }
+
     pub fn blocks<'a>(&'a self) -> impl iter::Iterator<Item = (BlockIndex, Block)> + 'a {
         (&self.indices)
             .iter()
             .map(move |&index| (index, self.block))
     }
 
-    fn transform(&self, mut transform: impl FnMut(BlockIndex) -> BlockIndex) -> Self {
+    fn transform(
+        &self,
+        size: BlockGridSize,
+        mut transform: impl FnMut(BlockIndex) -> BlockIndex,
+    ) -> Self {
         Piece {
             indices: self
                 .indices
                 .iter()
                 .map(|index| transform(*index))
                 .collect::<Vec<_>>(),
-            size: self.size,
+            size,
             block: self.block,
+            spawn_offset: self.spawn_offset,
         }
     }
 
+    fn rotated_size(&self) -> BlockGridSize {
+        BlockGridSize::new(self.size().height, self.size().width)
+    }
+
     pub fn rotate_left(&self) -> Self {
-        self.transform(|index| BlockIndex::new(self.size().width - 1 - index.y, index.x))
+        self.transform(self.rotated_size(), |index| {
+            BlockIndex::new(self.size().height - 1 - index.y, index.x)
+        })
     }
 
     pub fn rotate_right(&self) -> Self {
-        self.transform(|index| BlockIndex::new(index.y, self.size().height - 1 - index.x))
+        self.transform(self.rotated_size(), |index| {
+            BlockIndex::new(index.y, self.size().width - 1 - index.x)
+        })
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        self.transform(self.size(), |index| {
+            BlockIndex::new(
+                self.size().width - 1 - index.x,
+                self.size().height - 1 - index.y,
+            )
+        })
     }
 }
 
@@ -52,13 +142,15 @@ pub fn standards() -> Vec<Piece> {
     ]
     .iter()
     .enumerate()
-    .map(|(number, &(size, indices))| Piece {
-        size: BlockGridSize::new(size, size),
-        block: Block::new(number as u32),
-        indices: indices
-            .iter()
-            .map(|&(x, y)| BlockIndex::new(x, y))
-            .collect::<Vec<_>>(),
+    .map(|(number, &(size, indices))| {
+        Piece::new(
+            BlockGridSize::new(size, size),
+            Block::new(number as u32),
+            indices
+                .iter()
+                .map(|&(x, y)| BlockIndex::new(x, y))
+                .collect::<Vec<_>>(),
+        )
     })
     .collect()
 }
@@ -1,5 +1,7 @@
 use super::piece::Piece;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use std::collections;
 
 #[derive(Debug, Clone)]
@@ -7,21 +9,38 @@ pub struct PieceGenerator {
     source: Vec<Piece>,
     current_index: usize,
     stocks: collections::VecDeque<Piece>,
+    rng: StdRng,
 }
 
 impl PieceGenerator {
-    pub fn new(source: Vec<Piece>) -> Self {
+    fn with_rng(source: Vec<Piece>, rng: StdRng) -> Self {
         Self {
             current_index: source.len(),
             source,
             stocks: Default::default(),
+            rng,
+        }
+    }
+
+    pub fn new(source: Vec<Piece>) -> Self {
+        Self::with_rng(source, StdRng::from_entropy())
+    }
+
+    pub fn with_seed(source: Vec<Piece>, seed: u64) -> Self {
+        Self::with_rng(source, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_stocks(source: Vec<Piece>, stocks: Vec<Piece>) -> Self {
+        Self {
+            stocks: stocks.into(),
+            ..Self::new(source)
         }
     }
 
     fn generate(&mut self) {
         if self.current_index >= self.source.len() {
             self.current_index = 0;
-            self.source.shuffle(&mut rand::thread_rng());
+            self.source.shuffle(&mut self.rng);
         }
         self.stocks
             .push_back(self.source[self.current_index].clone());
@@ -33,6 +52,10 @@ impl PieceGenerator {
         self.stocks.pop_front().unwrap()
     }
 
+    pub fn put_back(&mut self, piece: Piece) {
+        self.stocks.push_front(piece);
+    }
+
     pub fn peek(&mut self, count: usize) -> Vec<Piece> {
         for _ in self.stocks.len()..count {
             self.generate();
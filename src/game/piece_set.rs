@@ -0,0 +1,260 @@
+use super::block::{Block, BlockGridSize, BlockIndex, BlockNumber};
+use super::piece::{self, Piece};
+use super::polyomino::{self, Symmetry};
+use serde::{Deserialize, Serialize};
+use std::collections;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path;
+
+#[derive(Debug)]
+pub enum PieceSetError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    NoPieces,
+    NoCells { piece: String },
+    TooWide { piece: String, width: usize, stage_width: usize },
+    CellOutOfBounds { piece: String, cell: [usize; 2] },
+    OverlappingCells { piece: String, cell: [usize; 2] },
+}
+
+impl fmt::Display for PieceSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PieceSetError::*;
+        match self {
+            Io(error) => write!(f, "can not read the piece set: {}", error),
+            Parse(error) => write!(f, "can not parse the piece set: {}", error),
+            NoPieces => write!(f, "the piece set has no pieces"),
+            NoCells { piece } => write!(f, "the piece {} has no cells", piece),
+            TooWide {
+                piece,
+                width,
+                stage_width,
+            } => write!(
+                f,
+                "the piece {} is {} blocks wide but the stage is only {} blocks wide",
+                piece, width, stage_width
+            ),
+            CellOutOfBounds { piece, cell } => write!(
+                f,
+                "the cell ({}, {}) of the piece {} is out of its bounds",
+                cell[0], cell[1], piece
+            ),
+            OverlappingCells { piece, cell } => write!(
+                f,
+                "the cell ({}, {}) of the piece {} is defined more than once",
+                cell[0], cell[1], piece
+            ),
+        }
+    }
+}
+
+impl error::Error for PieceSetError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PieceSetError::Io(error) => Some(error),
+            PieceSetError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PieceSetError {
+    fn from(error: io::Error) -> Self {
+        PieceSetError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PieceSetError {
+    fn from(error: serde_json::Error) -> Self {
+        PieceSetError::Parse(error)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PieceSet {
+    name: String,
+    pieces: Vec<Piece>,
+}
+
+impl PieceSet {
+    pub fn new(name: &str, pieces: Vec<Piece>) -> Result<Self, PieceSetError> {
+        if pieces.is_empty() {
+            return Err(PieceSetError::NoPieces);
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            pieces,
+        })
+    }
+
+    pub fn standard() -> Self {
+        Self {
+            name: "standard".to_owned(),
+            pieces: piece::standards(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    pub fn into_pieces(self) -> Vec<Piece> {
+        self.pieces
+    }
+}
+
+impl Default for PieceSet {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct PieceDefinition {
+    pub name: String,
+    pub size: [usize; 2],
+    pub cells: Vec<[usize; 2]>,
+    pub block: BlockNumber,
+    #[serde(default)]
+    pub spawn_offset: [isize; 2],
+}
+
+impl PieceDefinition {
+    pub fn to_piece(&self, stage_size: BlockGridSize) -> Result<Piece, PieceSetError> {
+        if self.cells.is_empty() {
+            return Err(PieceSetError::NoCells {
+                piece: self.name.clone(),
+            });
+        }
+        if self.size[0] > stage_size.width {
+            return Err(PieceSetError::TooWide {
+                piece: self.name.clone(),
+                width: self.size[0],
+                stage_width: stage_size.width,
+            });
+        }
+        let mut cells = collections::HashSet::new();
+        for &cell in &self.cells {
+            if cell[0] >= self.size[0] || cell[1] >= self.size[1] {
+                return Err(PieceSetError::CellOutOfBounds {
+                    piece: self.name.clone(),
+                    cell,
+                });
+            }
+            if !cells.insert(cell) {
+                return Err(PieceSetError::OverlappingCells {
+                    piece: self.name.clone(),
+                    cell,
+                });
+            }
+        }
+        Ok(Piece::new(
+            BlockGridSize::new(self.size[0], self.size[1]),
+            Block::new(self.block),
+            self.cells
+                .iter()
+                .map(|&[x, y]| BlockIndex::new(x, y))
+                .collect(),
+        )
+        .with_spawn_offset(euclid::TypedVector2D::new(
+            self.spawn_offset[0],
+            self.spawn_offset[1],
+        )))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct PieceSetDefinition {
+    pub name: String,
+    pub pieces: Vec<PieceDefinition>,
+}
+
+impl PieceSetDefinition {
+    pub fn to_piece_set(&self, stage_size: BlockGridSize) -> Result<PieceSet, PieceSetError> {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|piece| piece.to_piece(stage_size))
+            .collect::<Result<_, _>>()?;
+        PieceSet::new(&self.name, pieces)
+    }
+}
+
+const POLYOMINO_NAMES: [&str; 6] = [
+    "monomino",
+    "domino",
+    "tromino",
+    "tetromino",
+    "pentomino",
+    "hexomino",
+];
+
+pub fn builtin(name: &str) -> Option<PieceSet> {
+    if name == "standard" {
+        return Some(PieceSet::standard());
+    }
+    let (polyomino_name, symmetry) = match name.strip_prefix("free-") {
+        Some(polyomino_name) => (polyomino_name, Symmetry::Free),
+        None => (name, Symmetry::OneSided),
+    };
+    POLYOMINO_NAMES
+        .iter()
+        .position(|&builtin_name| builtin_name == polyomino_name)
+        .map(|index| PieceSet {
+            name: name.to_owned(),
+            pieces: polyomino::pieces(index + 1, symmetry),
+        })
+}
+
+pub fn parse(source: &str, stage_size: BlockGridSize) -> Result<PieceSet, PieceSetError> {
+    serde_json::from_str::<PieceSetDefinition>(source)?.to_piece_set(stage_size)
+}
+
+pub fn load(path: &path::Path, stage_size: BlockGridSize) -> Result<PieceSet, PieceSetError> {
+    parse(&fs::read_to_string(path)?, stage_size)
+}
+
+pub fn builtin_or_load(name: &str, stage_size: BlockGridSize) -> Result<PieceSet, PieceSetError> {
+    builtin(name).map_or_else(|| load(path::Path::new(name), stage_size), Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_name_of_parsed_sets() {
+        let source = r#"{
+            "name": "dominoes",
+            "pieces": [{ "name": "domino", "size": [2, 2], "block": 1, "cells": [[0, 1], [1, 1]] }]
+        }"#;
+        let piece_set = parse(source, BlockGridSize::new(10, 20)).unwrap();
+        assert_eq!(piece_set.name(), "dominoes");
+        assert_eq!(piece_set.pieces().len(), 1);
+        assert_eq!(builtin("free-tromino").unwrap().name(), "free-tromino");
+    }
+
+    #[test]
+    fn rejects_pieces_wider_than_the_stage() {
+        let source = r#"{
+            "name": "wide",
+            "pieces": [{ "name": "bar", "size": [12, 1], "block": 0, "cells": [[0, 0], [11, 0]] }]
+        }"#;
+        match parse(source, BlockGridSize::new(10, 20)) {
+            Err(PieceSetError::TooWide {
+                width: 12,
+                stage_width: 10,
+                ..
+            }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(parse(source, BlockGridSize::new(12, 20)).is_ok());
+    }
+}
@@ -0,0 +1,183 @@
+use super::event::GameEvent;
+use super::{Game, PieceState, NO_KICKS, ROTATION_180_KICKS};
+use serde::{Deserialize, Serialize};
+use std::collections;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PieceInput {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateLeft,
+    RotateRight,
+    Rotate180,
+}
+
+pub(super) const MOVING_INPUTS: [PieceInput; 6] = [
+    PieceInput::MoveLeft,
+    PieceInput::MoveRight,
+    PieceInput::SoftDrop,
+    PieceInput::RotateLeft,
+    PieceInput::RotateRight,
+    PieceInput::Rotate180,
+];
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Placement {
+    pub piece_state: PieceState,
+    pub inputs: Vec<PieceInput>,
+}
+
+impl Game {
+    pub(super) fn piece_state_after_input(
+        &self,
+        piece_state: &PieceState,
+        input: PieceInput,
+    ) -> Option<PieceState> {
+        use PieceInput::*;
+        match input {
+            MoveLeft => self.moved_piece_state(piece_state, euclid::TypedVector2D::new(-1, 0)),
+            MoveRight => self.moved_piece_state(piece_state, euclid::TypedVector2D::new(1, 0)),
+            SoftDrop => self.moved_piece_state(piece_state, euclid::TypedVector2D::new(0, -1)),
+            HardDrop => Some(PieceState::new(
+                piece_state.piece.clone(),
+                self.hard_drop_position(piece_state),
+            )),
+            RotateLeft => {
+                self.changed_piece_state(piece_state, piece_state.piece.rotate_left(), &NO_KICKS)
+            }
+            RotateRight => {
+                self.changed_piece_state(piece_state, piece_state.piece.rotate_right(), &NO_KICKS)
+            }
+            Rotate180 => self.changed_piece_state(
+                piece_state,
+                piece_state.piece.rotate_180(),
+                &ROTATION_180_KICKS,
+            ),
+        }
+    }
+
+    pub fn reachable_placements(&self) -> Vec<Placement> {
+        self.reachable_placements_from(&self.piece_state)
+    }
+
+    pub(super) fn reachable_placements_from(&self, piece_state: &PieceState) -> Vec<Placement> {
+        let mut placements = vec![];
+        if !self.can_put_piece_state(piece_state) {
+            return placements;
+        }
+        let mut paths = collections::HashMap::new();
+        let mut queue = collections::VecDeque::new();
+        let mut landed = collections::HashSet::new();
+        paths.insert(piece_state.clone(), vec![]);
+        queue.push_back(piece_state.clone());
+        while let Some(piece_state) = queue.pop_front() {
+            let inputs = paths[&piece_state].clone();
+            let landing = self
+                .piece_state_after_input(&piece_state, PieceInput::HardDrop)
+                .unwrap();
+            if landed.insert(landing.clone()) {
+                let mut inputs = inputs.clone();
+                inputs.push(PieceInput::HardDrop);
+                placements.push(Placement {
+                    piece_state: landing,
+                    inputs,
+                });
+            }
+            for &input in MOVING_INPUTS.iter() {
+                if let Some(next) = self.piece_state_after_input(&piece_state, input) {
+                    if !paths.contains_key(&next) {
+                        let mut inputs = inputs.clone();
+                        inputs.push(input);
+                        paths.insert(next.clone(), inputs);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        placements
+    }
+
+    pub fn apply_piece_input(&mut self, input: PieceInput) -> Vec<GameEvent> {
+        use PieceInput::*;
+        match input {
+            MoveLeft => self.move_piece_left(),
+            MoveRight => self.move_piece_right(),
+            SoftDrop => self.drop_piece_soft(),
+            HardDrop => self.drop_piece_hard(),
+            RotateLeft => self.rotate_piece_left(),
+            RotateRight => self.rotate_piece_right(),
+            Rotate180 => self.rotate_piece_180(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{piece, Block, BlockGridSize, BlockIndex, BlockIndexOffset, Stage};
+
+    fn game_with_stage(stage: Stage) -> Game {
+        let mut game = Game::new();
+        game.piece_state =
+            PieceState::with_initial_position(piece::standards()[0].clone(), stage.size());
+        game.stage = stage;
+        game
+    }
+
+    fn cells(piece_state: &PieceState) -> Vec<(isize, isize)> {
+        let mut cells = piece_state
+            .blocks()
+            .map(|(index, _)| (index.x, index.y))
+            .collect::<Vec<_>>();
+        cells.sort_unstable();
+        cells
+    }
+
+    fn find_placement(game: &Game, expected_cells: &[(isize, isize)]) -> Placement {
+        game.reachable_placements()
+            .into_iter()
+            .find(|placement| cells(&placement.piece_state) == expected_cells)
+            .expect("the placement is not reachable")
+    }
+
+    #[test]
+    fn finds_tucks_under_overhangs() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in 0..6 {
+            stage.put_block(BlockIndex::new(x, 1), Block::new(7));
+        }
+        let game = game_with_stage(stage);
+        let placement = find_placement(&game, &[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert!(placement.inputs.contains(&PieceInput::SoftDrop));
+        let mut replayed = game.clone();
+        for &input in &placement.inputs {
+            replayed.apply_piece_input(input);
+        }
+        for x in 0..4 {
+            assert!(!replayed.stage.can_put_to(BlockIndexOffset::new(x, 0)));
+        }
+    }
+
+    #[test]
+    fn returns_shortest_paths() {
+        let game = game_with_stage(Stage::new(BlockGridSize::new(10, 20)));
+        assert_eq!(
+            find_placement(&game, &[(3, 0), (4, 0), (5, 0), (6, 0)]).inputs,
+            vec![PieceInput::HardDrop]
+        );
+        assert_eq!(
+            find_placement(&game, &[(1, 0), (2, 0), (3, 0), (4, 0)]).inputs,
+            vec![
+                PieceInput::MoveLeft,
+                PieceInput::MoveLeft,
+                PieceInput::HardDrop
+            ]
+        );
+        let inputs = find_placement(&game, &[(9, 0), (9, 1), (9, 2), (9, 3)]).inputs;
+        assert_eq!(inputs.len(), 6);
+        assert_eq!(inputs.last(), Some(&PieceInput::HardDrop));
+    }
+}
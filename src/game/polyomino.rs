@@ -0,0 +1,144 @@
+use super::block::{Block, BlockGridSize, BlockIndex};
+use super::piece::Piece;
+use std::collections;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Symmetry {
+    Free,
+    OneSided,
+}
+
+type Cells = Vec<(isize, isize)>;
+
+const NEIGHBORS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+fn normalize(mut cells: Cells) -> Cells {
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    for cell in cells.iter_mut() {
+        *cell = (cell.0 - min_x, cell.1 - min_y);
+    }
+    cells.sort_unstable();
+    cells
+}
+
+fn rotate(cells: &[(isize, isize)]) -> Cells {
+    normalize(cells.iter().map(|&(x, y)| (-y, x)).collect())
+}
+
+fn reflect(cells: &[(isize, isize)]) -> Cells {
+    normalize(cells.iter().map(|&(x, y)| (-x, y)).collect())
+}
+
+fn rotations(cells: &[(isize, isize)]) -> Vec<Cells> {
+    let mut rotations = vec![normalize(cells.to_vec())];
+    for _ in 1..4 {
+        let next = rotate(rotations.last().unwrap());
+        rotations.push(next);
+    }
+    rotations
+}
+
+fn canonical(cells: &[(isize, isize)], symmetry: Symmetry) -> Cells {
+    let mut orientations = rotations(cells);
+    if symmetry == Symmetry::Free {
+        orientations.append(&mut rotations(&reflect(cells)));
+    }
+    orientations.into_iter().min().unwrap()
+}
+
+fn extent(cells: &[(isize, isize)]) -> (usize, usize) {
+    (
+        cells
+            .iter()
+            .map(|&(x, _)| x as usize + 1)
+            .max()
+            .unwrap_or(0),
+        cells
+            .iter()
+            .map(|&(_, y)| y as usize + 1)
+            .max()
+            .unwrap_or(0),
+    )
+}
+
+pub fn enumerate(size: usize, symmetry: Symmetry) -> Vec<Vec<(isize, isize)>> {
+    if size == 0 {
+        return vec![];
+    }
+    let mut shapes = collections::BTreeSet::new();
+    shapes.insert(vec![(0, 0)]);
+    for _ in 1..size {
+        let mut next_shapes = collections::BTreeSet::new();
+        for shape in &shapes {
+            for &(x, y) in shape {
+                for &(dx, dy) in NEIGHBORS.iter() {
+                    let cell = (x + dx, y + dy);
+                    if !shape.contains(&cell) {
+                        let mut next = shape.clone();
+                        next.push(cell);
+                        next_shapes.insert(canonical(&next, symmetry));
+                    }
+                }
+            }
+        }
+        shapes = next_shapes;
+    }
+    shapes.into_iter().collect()
+}
+
+fn spawn_orientation(cells: &[(isize, isize)]) -> Cells {
+    rotations(cells)
+        .into_iter()
+        .filter(|cells| {
+            let (width, height) = extent(cells);
+            width >= height
+        })
+        .min()
+        .unwrap()
+}
+
+fn to_piece(cells: &[(isize, isize)], block: Block) -> Piece {
+    let cells = spawn_orientation(cells);
+    let (width, height) = extent(&cells);
+    let side = width.max(height);
+    let offset_x = (side - width) / 2;
+    let offset_y = (side - height).div_ceil(2);
+    Piece::new(
+        BlockGridSize::new(side, side),
+        block,
+        cells
+            .iter()
+            .map(|&(x, y)| BlockIndex::new(x as usize + offset_x, y as usize + offset_y))
+            .collect(),
+    )
+}
+
+pub fn pieces(size: usize, symmetry: Symmetry) -> Vec<Piece> {
+    enumerate(size, symmetry)
+        .iter()
+        .enumerate()
+        .map(|(number, cells)| to_piece(cells, Block::new(number as u32)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(symmetry: Symmetry) -> Vec<usize> {
+        (1..=6)
+            .map(|size| enumerate(size, symmetry).len())
+            .collect()
+    }
+
+    #[test]
+    fn enumerates_one_sided_polyominoes() {
+        assert_eq!(counts(Symmetry::OneSided), vec![1, 1, 2, 7, 18, 60]);
+    }
+
+    #[test]
+    fn enumerates_free_polyominoes() {
+        assert_eq!(counts(Symmetry::Free), vec![1, 1, 2, 5, 12, 35]);
+    }
+}
@@ -0,0 +1,142 @@
+use super::block::{Block, GARBAGE_BLOCK_NUMBER};
+use super::event::GameEvent;
+use super::mode::GameMode;
+use super::timing::Frames;
+use super::Game;
+use rand::Rng;
+
+pub(super) const INITIAL_RISE_INTERVAL: Frames = 600;
+const MIN_RISE_INTERVAL: Frames = 60;
+
+impl Game {
+    pub fn rise_interval(&self) -> Frames {
+        self.rise_interval
+    }
+
+    pub fn frames_until_rise(&self) -> Frames {
+        self.rise_wait
+    }
+
+    pub fn with_rising_floor(self, frames_until_rise: Frames, rise_interval: Frames) -> Self {
+        Self {
+            mode: GameMode::Survival,
+            rise_wait: frames_until_rise,
+            rise_interval,
+            ..self
+        }
+    }
+
+    fn raise_floor(&mut self) -> Vec<GameEvent> {
+        let size = self.stage_size();
+        let hole = self.rng.gen_range(0, size.width);
+        let row = (0..size.width)
+            .map(|x| {
+                if x == hole {
+                    None
+                } else {
+                    Some(Block::new(GARBAGE_BLOCK_NUMBER))
+                }
+            })
+            .collect::<Vec<_>>();
+        let (move_results, put_results) = match self.stage.insert_bottom_row(&row) {
+            Some(results) => results,
+            None => {
+                self.is_game_over = true;
+                return vec![GameEvent::GameOver];
+            }
+        };
+        let mut events = vec![
+            GameEvent::MoveBlocks(move_results),
+            GameEvent::PutBlocks(put_results),
+        ];
+        while !self.can_put_piece_state(&self.piece_state) {
+            self.piece_state.position.y += 1;
+        }
+        events.push(self.move_piece_event());
+        events.append(&mut self.update_piece_phase());
+        events
+    }
+
+    pub(super) fn tick_floor(&mut self) -> Vec<GameEvent> {
+        self.rise_wait = self.rise_wait.saturating_sub(1);
+        if self.rise_wait > 0 || !self.phase.has_piece() {
+            return vec![];
+        }
+        self.rise_interval = (self.rise_interval * 9 / 10).max(MIN_RISE_INTERVAL);
+        self.rise_wait = self.rise_interval;
+        self.raise_floor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{piece, BlockGridSize, BlockIndex, Phase, PieceSet, PieceState, Stage};
+
+    fn survival_game(stage: Stage) -> Game {
+        let pieces = piece::standards();
+        let piece_state = PieceState::with_initial_position(pieces[0].clone(), stage.size());
+        Game::from_position(PieceSet::standard(), stage, piece_state, pieces, None, true)
+            .with_rising_floor(INITIAL_RISE_INTERVAL, INITIAL_RISE_INTERVAL)
+    }
+
+    #[test]
+    fn raises_a_garbage_row_with_one_hole() {
+        let mut game = survival_game(Stage::new(BlockGridSize::new(10, 20)));
+        game.rise_wait = 2;
+        game.tick();
+        assert_eq!(game.stage().row_mask(0), 0);
+        game.tick();
+        let garbage = game
+            .stage()
+            .blocks()
+            .map(|(index, block)| {
+                assert_eq!(index.y, 0);
+                block.number
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(garbage, vec![GARBAGE_BLOCK_NUMBER; 9]);
+        assert_eq!(game.rise_interval(), 540);
+        assert_eq!(game.frames_until_rise(), 540);
+    }
+
+    #[test]
+    fn pushes_the_piece_up() {
+        let mut game = survival_game(Stage::new(BlockGridSize::new(10, 20)));
+        while game.phase() == Phase::Falling {
+            game.drop_piece_soft();
+        }
+        let y = game.piece_state().position.y;
+        game.rise_wait = 1;
+        game.tick();
+        assert_eq!(game.piece_state().position.y, y + 1);
+        assert_eq!(game.phase(), Phase::Locking);
+        assert!(game.can_put_piece_state(game.piece_state()));
+    }
+
+    #[test]
+    fn shortens_the_interval_down_to_the_minimum() {
+        let mut game =
+            survival_game(Stage::new(BlockGridSize::new(10, 20))).with_rising_floor(1, 100);
+        let mut intervals = vec![];
+        for _ in 0..5 {
+            game.rise_wait = 1;
+            game.tick();
+            intervals.push(game.rise_interval());
+        }
+        assert_eq!(intervals, vec![90, 81, 72, 64, 60]);
+    }
+
+    #[test]
+    fn tops_out_when_the_top_row_has_blocks() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        stage.put_block(BlockIndex::new(0, 19), Block::new(1));
+        let mut game = survival_game(stage).with_rising_floor(1, INITIAL_RISE_INTERVAL);
+        let events = game.tick();
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameOver)));
+        assert!(game.is_game_over());
+        assert_eq!(game.stage().row_mask(0), 0);
+    }
+}
@@ -1,15 +1,34 @@
 use super::block::{Block, BlockGrid, BlockGridSize, BlockIndex, BlockIndexOffset};
 use super::event::{MoveResult, PutResult, RemoveResult};
+use std::collections;
+use std::iter;
+
+pub type RowMask = u64;
+
+pub const MAX_WIDTH: usize = RowMask::BITS as usize;
+
+fn column_mask(x: usize) -> RowMask {
+    1 << x
+}
 
 #[derive(Debug, Clone)]
 pub struct Stage {
     blocks: BlockGrid,
+    rows: Vec<RowMask>,
+    filled_row_mask: RowMask,
 }
 
 impl Stage {
     pub fn new(size: BlockGridSize) -> Self {
+        assert!(
+            size.width > 0 && size.width <= MAX_WIDTH,
+            "the stage must be 1 to {} blocks wide",
+            MAX_WIDTH
+        );
         Self {
             blocks: BlockGrid::new(size, None),
+            rows: vec![0; size.height],
+            filled_row_mask: RowMask::MAX >> (MAX_WIDTH - size.width),
         }
     }
 
@@ -17,16 +36,74 @@ impl Stage {
         self.blocks.size()
     }
 
+    pub fn row_mask(&self, y: usize) -> RowMask {
+        self.rows[y]
+    }
+
+    pub fn block(&self, index: BlockIndex) -> Option<Block> {
+        self.blocks[index]
+    }
+
+    pub fn blocks<'a>(&'a self) -> impl iter::Iterator<Item = (BlockIndex, Block)> + 'a {
+        use euclid_ext::Points;
+        euclid::TypedRect::from_size(self.size())
+            .points()
+            .filter_map(move |index| self.blocks[index].map(|block| (index, block)))
+    }
+
+    pub fn contains(&self, index: BlockIndexOffset) -> bool {
+        index.x >= 0
+            && (index.x as usize) < self.size().width
+            && index.y >= 0
+            && (index.y as usize) < self.size().height
+    }
+
+    fn neighbors<'a>(&'a self, index: BlockIndex) -> impl iter::Iterator<Item = BlockIndex> + 'a {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .map(move |&(x, y)| index.cast::<isize>() + euclid::TypedVector2D::new(x, y))
+            .filter(move |&neighbor| self.contains(neighbor))
+            .map(|neighbor| neighbor.cast::<usize>())
+    }
+
+    pub fn connected_groups(
+        &self,
+        is_connected: impl Fn(Block, Block) -> bool,
+    ) -> Vec<Vec<BlockIndex>> {
+        let mut visited = collections::HashSet::new();
+        let mut groups = vec![];
+        for (index, block) in self.blocks() {
+            if !visited.insert(index) {
+                continue;
+            }
+            let mut group = vec![index];
+            let mut stack = vec![(index, block)];
+            while let Some((index, block)) = stack.pop() {
+                for neighbor in self.neighbors(index) {
+                    if let Some(neighbor_block) = self.block(neighbor) {
+                        if is_connected(block, neighbor_block) && visited.insert(neighbor) {
+                            group.push(neighbor);
+                            stack.push((neighbor, neighbor_block));
+                        }
+                    }
+                }
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
     pub fn can_put_to(&self, index: BlockIndexOffset) -> bool {
         index.x >= 0
             && (index.x as usize) < self.size().width
             && index.y >= 0
             && (index.y as usize >= self.size().height
-                || self.blocks[index.cast::<usize>()].is_none())
+                || self.rows[index.y as usize] & column_mask(index.x as usize) == 0)
     }
 
     pub fn put_block(&mut self, index: BlockIndex, block: Block) -> PutResult {
         self.blocks[index] = Some(block);
+        self.rows[index.y] |= column_mask(index.x);
         PutResult {
             block: block,
             index: index,
@@ -34,20 +111,20 @@ impl Stage {
     }
 
     pub fn is_filled_line(&self, y: usize) -> bool {
-        self.blocks
-            .line(y)
-            .unwrap()
-            .iter()
-            .all(|block| block.is_some())
+        self.rows[y] == self.filled_row_mask
     }
 
     pub fn filled_line_indices(&self) -> Vec<usize> {
-        (0..self.size().height)
-            .filter(|&y| self.is_filled_line(y))
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|&(_, &row)| row == self.filled_row_mask)
+            .map(|(y, _)| y)
             .collect::<Vec<_>>()
     }
 
     pub fn remove_block(&mut self, index: BlockIndex) -> Option<RemoveResult> {
+        self.rows[index.y] &= !column_mask(index.x);
         std::mem::replace(&mut self.blocks[index], None).map(|block| RemoveResult { block, index })
     }
 
@@ -75,7 +152,9 @@ impl Stage {
         destination: BlockIndex,
     ) -> Option<MoveResult> {
         std::mem::replace(&mut self.blocks[source], None).map(|block| {
+            self.rows[source.y] &= !column_mask(source.x);
             self.blocks[destination] = Some(block);
+            self.rows[destination.y] |= column_mask(destination.x);
             MoveResult {
                 block,
                 source,
@@ -96,13 +175,97 @@ impl Stage {
         results
     }
 
+    pub fn insert_bottom_row(
+        &mut self,
+        row: &[Option<Block>],
+    ) -> Option<(Vec<MoveResult>, Vec<PutResult>)> {
+        if self.rows[self.size().height - 1] != 0 {
+            return None;
+        }
+        let mut move_results = vec![];
+        for y in (0..self.size().height - 1).rev() {
+            move_results.append(&mut self.move_line(y, y + 1));
+        }
+        let put_results = row
+            .iter()
+            .take(self.size().width)
+            .enumerate()
+            .filter_map(|(x, block)| {
+                block.map(|block| self.put_block(BlockIndex::new(x, 0), block))
+            })
+            .collect();
+        Some((move_results, put_results))
+    }
+
     pub fn remove_filled_lines(&mut self) -> Option<(Vec<RemoveResult>, Vec<MoveResult>)> {
         let line_indices = self.filled_line_indices();
         let remove_results = self.remove_lines(&line_indices);
         if remove_results.is_empty() {
             return None;
         }
-        let mut line_boundaries = line_indices;
+        Some((remove_results, self.collapse_lines(&line_indices)))
+    }
+
+    fn fall_distance(&self, group: &[BlockIndex]) -> usize {
+        let cells = group.iter().collect::<collections::HashSet<_>>();
+        let mut distance = 0;
+        while group.iter().all(|index| {
+            index.y > distance && {
+                let below = BlockIndex::new(index.x, index.y - distance - 1);
+                self.block(below).is_none() || cells.contains(&below)
+            }
+        }) {
+            distance += 1;
+        }
+        distance
+    }
+
+    pub fn drop_groups(&mut self) -> Vec<MoveResult> {
+        let mut move_results = vec![];
+        loop {
+            let mut groups = self.connected_groups(|_, _| true);
+            groups.sort_by_key(|group| group.iter().map(|index| index.y).min());
+            let mut has_moved = false;
+            for mut group in groups {
+                let distance = self.fall_distance(&group);
+                if distance == 0 {
+                    continue;
+                }
+                group.sort_by_key(|index| index.y);
+                for index in group {
+                    move_results.extend(
+                        self.move_block(index, BlockIndex::new(index.x, index.y - distance)),
+                    );
+                }
+                has_moved = true;
+            }
+            if !has_moved {
+                break;
+            }
+        }
+        move_results
+    }
+
+    pub fn drop_blocks(&mut self) -> Vec<MoveResult> {
+        let mut move_results = vec![];
+        for x in 0..self.size().width {
+            let mut destination = 0;
+            for y in 0..self.size().height {
+                if self.block(BlockIndex::new(x, y)).is_some() {
+                    if y != destination {
+                        move_results.extend(
+                            self.move_block(BlockIndex::new(x, y), BlockIndex::new(x, destination)),
+                        );
+                    }
+                    destination += 1;
+                }
+            }
+        }
+        move_results
+    }
+
+    pub fn collapse_lines(&mut self, line_indices: &[usize]) -> Vec<MoveResult> {
+        let mut line_boundaries = line_indices.to_vec();
         line_boundaries.push(self.size().height);
         let mut move_results = vec![];
         for (source, destination) in line_boundaries
@@ -113,6 +276,113 @@ impl Stage {
         {
             move_results.append(&mut self.move_line(source, destination));
         }
-        Some((remove_results, move_results))
+        move_results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rows_match_blocks(stage: &Stage) {
+        for y in 0..stage.size().height {
+            let mask = (0..stage.size().width)
+                .filter(|&x| stage.block(BlockIndex::new(x, y)).is_some())
+                .fold(0, |mask, x| mask | column_mask(x));
+            assert_eq!(stage.row_mask(y), mask, "row {} is out of sync", y);
+        }
+    }
+
+    #[test]
+    fn keeps_row_masks_in_sync_with_blocks() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in 0..4 {
+            stage.put_block(BlockIndex::new(x, 0), Block::new(1));
+            stage.put_block(BlockIndex::new(x + 3, 2), Block::new(2));
+        }
+        assert_rows_match_blocks(&stage);
+
+        stage.remove_block(BlockIndex::new(1, 0));
+        stage.remove_block(BlockIndex::new(9, 5));
+        assert_rows_match_blocks(&stage);
+
+        stage.move_block(BlockIndex::new(3, 2), BlockIndex::new(8, 4));
+        stage.move_block(BlockIndex::new(0, 7), BlockIndex::new(0, 8));
+        assert_rows_match_blocks(&stage);
+
+        let mut row = vec![Some(Block::new(3)); 10];
+        row[4] = None;
+        assert!(stage.insert_bottom_row(&row).is_some());
+        assert_rows_match_blocks(&stage);
+        assert_eq!(stage.row_mask(0), 0b11_1110_1111);
+
+        stage.put_block(BlockIndex::new(2, 19), Block::new(1));
+        assert!(stage.insert_bottom_row(&row).is_none());
+        assert_rows_match_blocks(&stage);
+    }
+
+    #[test]
+    fn fills_rows_as_wide_as_the_mask() {
+        let mut stage = Stage::new(BlockGridSize::new(MAX_WIDTH, 2));
+        for x in 0..MAX_WIDTH {
+            stage.put_block(BlockIndex::new(x, 0), Block::new(1));
+        }
+        stage.put_block(BlockIndex::new(0, 1), Block::new(1));
+        assert_eq!(stage.filled_line_indices(), vec![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the stage must be 1 to 64 blocks wide")]
+    fn rejects_empty_stages() {
+        Stage::new(BlockGridSize::new(0, 20));
+    }
+
+    #[test]
+    fn drops_floating_groups_as_a_unit() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 6));
+        stage.put_block(BlockIndex::new(2, 0), Block::new(1));
+        for &(x, y) in &[(2, 3), (3, 3), (3, 4)] {
+            stage.put_block(BlockIndex::new(x, y), Block::new(2));
+        }
+        assert_eq!(stage.drop_groups().len(), 3);
+        let blocks = stage
+            .blocks()
+            .map(|(index, block)| (index.x, index.y, block.number))
+            .collect::<collections::HashSet<_>>();
+        let expected = [(2, 0, 1), (2, 1, 2), (3, 1, 2), (3, 2, 2)];
+        assert_eq!(blocks, expected.iter().copied().collect());
+        assert_rows_match_blocks(&stage);
+    }
+
+    #[test]
+    fn drops_blocks_within_their_columns() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 6));
+        stage.put_block(BlockIndex::new(0, 0), Block::new(1));
+        stage.put_block(BlockIndex::new(0, 3), Block::new(2));
+        stage.put_block(BlockIndex::new(1, 2), Block::new(3));
+        stage.put_block(BlockIndex::new(1, 5), Block::new(4));
+        assert_eq!(stage.drop_blocks().len(), 3);
+        let blocks = stage
+            .blocks()
+            .map(|(index, block)| (index.x, index.y, block.number))
+            .collect::<collections::HashSet<_>>();
+        let expected = [(0, 0, 1), (0, 1, 2), (1, 0, 3), (1, 1, 4)];
+        assert_eq!(blocks, expected.iter().copied().collect());
+        assert_rows_match_blocks(&stage);
+    }
+
+    #[test]
+    fn detects_filled_lines_from_row_masks() {
+        let mut stage = Stage::new(BlockGridSize::new(10, 20));
+        for x in 0..10 {
+            stage.put_block(BlockIndex::new(x, 1), Block::new(1));
+        }
+        stage.put_block(BlockIndex::new(0, 2), Block::new(1));
+        assert_eq!(stage.filled_line_indices(), vec![1]);
+
+        stage.remove_filled_lines();
+        assert_rows_match_blocks(&stage);
+        assert!(stage.filled_line_indices().is_empty());
+        assert_eq!(stage.row_mask(1), column_mask(0));
     }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use super::block::BlockNumber;
+use super::piece::Piece;
+use super::timing::FRAME_RATE;
+use std::collections;
+use std::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameStats {
+    pub frames: u64,
+    pub placed_pieces: usize,
+    pub key_presses: usize,
+    pub line_clears: collections::BTreeMap<usize, usize>,
+    pub piece_counts: collections::BTreeMap<BlockNumber, usize>,
+    pub combo: usize,
+    pub max_combo: usize,
+    pub finesse_faults: usize,
+}
+
+fn clear_type_name(line_count: usize) -> String {
+    match line_count {
+        1 => "single".to_string(),
+        2 => "double".to_string(),
+        3 => "triple".to_string(),
+        4 => "quad".to_string(),
+        _ => format!("{}-line", line_count),
+    }
+}
+
+impl GameStats {
+    pub fn seconds(&self) -> f64 {
+        self.frames as f64 / FRAME_RATE as f64
+    }
+
+    pub fn pieces_per_second(&self) -> f64 {
+        if self.frames == 0 {
+            0.0
+        } else {
+            self.placed_pieces as f64 / self.seconds()
+        }
+    }
+
+    pub fn key_presses_per_piece(&self) -> f64 {
+        if self.placed_pieces == 0 {
+            0.0
+        } else {
+            self.key_presses as f64 / self.placed_pieces as f64
+        }
+    }
+
+    pub(super) fn record_generated_piece(&mut self, piece: &Piece) {
+        *self.piece_counts.entry(piece.block().number).or_insert(0) += 1;
+    }
+
+    pub(super) fn record_line_clear(&mut self, line_count: usize) {
+        *self.line_clears.entry(line_count).or_insert(0) += 1;
+    }
+
+    pub(super) fn record_lock(&mut self, has_cleared_blocks: bool) {
+        self.placed_pieces += 1;
+        if has_cleared_blocks {
+            self.combo += 1;
+            self.max_combo = self.max_combo.max(self.combo);
+        } else {
+            self.combo = 0;
+        }
+    }
+}
+
+impl fmt::Display for GameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "time: {:.1}s", self.seconds())?;
+        writeln!(
+            f,
+            "pieces: {} ({:.2} per second)",
+            self.placed_pieces,
+            self.pieces_per_second()
+        )?;
+        writeln!(
+            f,
+            "key presses: {} ({:.2} per piece)",
+            self.key_presses,
+            self.key_presses_per_piece()
+        )?;
+        writeln!(
+            f,
+            "line clears: {}",
+            self.line_clears
+                .iter()
+                .map(|(&line_count, count)| format!("{} {}", clear_type_name(line_count), count))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        )?;
+        writeln!(
+            f,
+            "pieces by block: {}",
+            self.piece_counts
+                .iter()
+                .map(|(number, count)| format!("{}: {}", number, count))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        )?;
+        writeln!(f, "max combo: {}", self.max_combo)?;
+        write!(f, "finesse faults: {}", self.finesse_faults)
+    }
+}
@@ -0,0 +1,115 @@
+use super::event::GameEvent;
+use super::Game;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SubscriptionId(usize);
+
+type Listener = Box<dyn FnMut(&GameEvent) + Send>;
+
+#[derive(Default)]
+pub struct Subscribers {
+    next_id: usize,
+    listeners: Vec<(SubscriptionId, Listener)>,
+}
+
+// Listeners belong to the game they subscribed to, so copies made for
+// history snapshots or bot searches start without any.
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.listeners.iter().map(|(id, _)| id))
+            .finish()
+    }
+}
+
+impl Game {
+    pub fn subscribe(
+        &mut self,
+        listener: impl FnMut(&GameEvent) + Send + 'static,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.subscribers.next_id);
+        self.subscribers.next_id += 1;
+        self.subscribers.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let listeners = &mut self.subscribers.listeners;
+        let len = listeners.len();
+        listeners.retain(|(listener_id, _)| *listener_id != id);
+        listeners.len() != len
+    }
+
+    pub(super) fn publish(&mut self, events: Vec<GameEvent>) -> Vec<GameEvent> {
+        for event in &events {
+            for (_, listener) in &mut self.subscribers.listeners {
+                listener(event);
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameMode;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn subscribe_channel(game: &mut Game) -> (SubscriptionId, mpsc::Receiver<GameEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let id = game.subscribe(move |event| sender.send(event.clone()).unwrap());
+        (id, receiver)
+    }
+
+    #[test]
+    fn publishes_events_to_every_subscriber() {
+        let mut game = Game::new();
+        let (_, first) = subscribe_channel(&mut game);
+        let (_, second) = subscribe_channel(&mut game);
+        let events = game.drop_piece_hard();
+        assert!(!events.is_empty());
+        assert_eq!(first.try_iter().collect::<Vec<_>>(), events);
+        assert_eq!(second.try_iter().collect::<Vec<_>>(), events);
+    }
+
+    #[test]
+    fn stops_publishing_after_unsubscribing() {
+        let mut game = Game::new();
+        let (id, receiver) = subscribe_channel(&mut game);
+        assert!(game.unsubscribe(id));
+        assert!(!game.unsubscribe(id));
+        game.drop_piece_hard();
+        assert_eq!(receiver.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn keeps_subscribers_across_undo_and_redo() {
+        let mut game = Game::with_mode(GameMode::Practice);
+        let (_, receiver) = subscribe_channel(&mut game);
+        game.drop_piece_hard();
+        receiver.try_iter().for_each(drop);
+        let events = game.undo();
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), events);
+        let events = game.redo();
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), events);
+    }
+
+    #[test]
+    fn moves_games_with_subscribers_between_threads() {
+        let mut game = Game::new();
+        let (_, receiver) = subscribe_channel(&mut game);
+        let events = thread::spawn(move || game.drop_piece_hard())
+            .join()
+            .unwrap();
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), events);
+    }
+}
@@ -0,0 +1,58 @@
+pub type Frames = u32;
+
+pub const FRAME_RATE: Frames = 60;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Timings {
+    pub gravity: Frames,
+    pub lock: Frames,
+    pub entry: Frames,
+    pub line_clear: Frames,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            gravity: 12,
+            lock: 12,
+            entry: 0,
+            line_clear: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn drops_the_piece_once_per_gravity_interval() {
+        let mut game = Game::new().with_timings(Timings {
+            gravity: 3,
+            ..Timings::default()
+        });
+        let y = game.piece_state().position.y;
+        game.tick();
+        game.tick();
+        assert_eq!(game.piece_state().position.y, y);
+        game.tick();
+        assert_eq!(game.piece_state().position.y, y - 1);
+        for _ in 0..3 {
+            game.tick();
+        }
+        assert_eq!(game.piece_state().position.y, y - 2);
+        assert_eq!(game.stats().frames, 6);
+    }
+
+    #[test]
+    fn stops_counting_frames_after_the_game_is_over() {
+        let mut game = Game::new();
+        while !game.is_game_over() {
+            game.drop_piece_hard();
+        }
+        let frames = game.stats().frames;
+        assert!(game.tick().is_empty());
+        assert_eq!(game.stats().frames, frames);
+    }
+}
@@ -1,17 +1,36 @@
 use crate::assets::{BlockFace, Texture};
+use crate::controls::{self, Action, ControlInput, Controls, KeyMap, PlayerInput};
 use crate::game::{
-    BlockGridSize, BlockIndex, BlockIndexOffset, BlockSpace, Game, GameEvent, MoveResult,
-    Piece, PutResult, RemoveResult,
+    BlockGridSize, BlockIndex, BlockIndexOffset, BlockSpace, Frames, Game, GameEvent, GameMode,
+    MoveResult, Piece, PutResult, RemoveResult, Timings, FRAME_RATE,
 };
+use crate::high_scores::{self, HighScore, HighScoreTable};
 use crate::scene_context::SceneContext;
 use crate::sprite_ext::{AddTo, MoveTo, MovedTo, PixelPosition, RemoveAllChildren, Sprite};
 use piston_window::*;
 use std::collections;
+use std::mem;
+
+pub const WINDOW_TITLE: &str = "( o_o)";
 
 const TILE_SIZE: f64 = 8.0;
 
 const SCALE: f64 = 2.0;
 
+const ENTRY_DELAY: Frames = 6;
+
+const LINE_CLEAR_DELAY: Frames = 18;
+
+const MAX_FRAMES_PER_UPDATE: Frames = 15;
+
+pub const DEFAULT_BLOCK_FADE_DELAY: f64 = 2.0;
+
+const BLOCK_FADE_DURATION: f64 = 0.5;
+
+const BLOCK_FLASH_DURATION: f64 = 0.5;
+
+const MAX_NAME_LENGTH: usize = 8;
+
 trait ToPixelSpace {
     type Output;
 
@@ -37,6 +56,22 @@ impl ToPixelSpace for BlockIndex {
     }
 }
 
+fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+struct Rebinding {
+    key_map: KeyMap,
+    action_index: usize,
+    keys: Vec<String>,
+}
+
+impl Rebinding {
+    fn action(&self) -> Action {
+        Action::ALL[self.action_index]
+    }
+}
+
 enum Job {
     GameEvent(GameEvent),
     Run(Box<dyn FnOnce(&mut GameScene)>),
@@ -61,7 +96,10 @@ struct Sprites {
     piece_sprite_id: uuid::Uuid,
     piece_guide_sprite_id: uuid::Uuid,
     next_pieces_sprite_id: uuid::Uuid,
+    hold_piece_sprite_id: uuid::Uuid,
+    text_sprite_id: uuid::Uuid,
     block_sprite_ids: array2d::Array2D<Option<uuid::Uuid>, BlockSpace>,
+    block_visible_times: array2d::Array2D<f64, BlockSpace>,
 }
 
 impl Sprites {
@@ -87,6 +125,17 @@ impl Sprites {
             context,
         );
         let next_pieces_sprite_id = root_sprite.add_child(next_pieces_sprite);
+        let mut hold_piece_sprite = context
+            .empty_sprite()
+            .moved_to(PixelPosition::new(TILE_SIZE * 0.25, TILE_SIZE * 1.0));
+        hold_piece_sprite.set_scale(0.75, 0.75);
+        put_background_tile_sprites(&mut hold_piece_sprite, BlockGridSize::new(4, 4), context);
+        let hold_piece_sprite_id = root_sprite.add_child(hold_piece_sprite);
+        let text_sprite_id = root_sprite.add_child(
+            context
+                .empty_sprite()
+                .moved_to(PixelPosition::new(TILE_SIZE * 4.0, TILE_SIZE * 2.0)),
+        );
         scene.add_child(root_sprite);
         Self {
             scene,
@@ -94,7 +143,10 @@ impl Sprites {
             piece_guide_sprite_id,
             stage_sprite_id,
             next_pieces_sprite_id,
+            hold_piece_sprite_id,
+            text_sprite_id,
             block_sprite_ids: array2d::Array2D::new(stage_size, None),
+            block_visible_times: array2d::Array2D::new(stage_size, 0.0),
         }
     }
 
@@ -118,6 +170,14 @@ impl Sprites {
         self.sprite(self.next_pieces_sprite_id).unwrap()
     }
 
+    pub fn hold_piece_sprite(&mut self) -> &mut Sprite {
+        self.sprite(self.hold_piece_sprite_id).unwrap()
+    }
+
+    pub fn text_sprite(&mut self) -> &mut Sprite {
+        self.sprite(self.text_sprite_id).unwrap()
+    }
+
     pub fn block_sprite_id(&self, index: BlockIndex) -> Option<uuid::Uuid> {
         self.block_sprite_ids[index]
     }
@@ -139,9 +199,39 @@ impl Sprites {
         self.block_sprite_ids[index] = Some(id);
     }
 
+    pub fn remove_all_block_sprites(&mut self) {
+        use euclid_ext::Points;
+        for index in euclid::TypedRect::from_size(self.block_sprite_ids.size()).points() {
+            self.remove_block_sprite(index);
+        }
+    }
+
     pub fn move_block_sprite_id(&mut self, source: BlockIndex, destination: BlockIndex) {
         if let Some(id) = std::mem::replace(&mut self.block_sprite_ids[source], None) {
             self.set_block_sprite_id(destination, id);
+            self.block_visible_times[destination] = self.block_visible_times[source];
+        }
+    }
+
+    pub fn set_block_visible_time(&mut self, index: BlockIndex, time: f64) {
+        self.block_visible_times[index] = time;
+    }
+
+    pub fn show_all_blocks(&mut self, time: f64) {
+        use euclid_ext::Points;
+        for index in euclid::TypedRect::from_size(self.block_visible_times.size()).points() {
+            self.set_block_visible_time(index, time);
+        }
+    }
+
+    pub fn update_block_opacities(&mut self, delta: f64) {
+        use euclid_ext::Points;
+        for index in euclid::TypedRect::from_size(self.block_visible_times.size()).points() {
+            let time = (self.block_visible_times[index] - delta).max(0.0);
+            self.block_visible_times[index] = time;
+            if let Some(sprite) = self.block_sprite(index) {
+                sprite.set_opacity((time / BLOCK_FADE_DURATION).min(1.0) as f32);
+            }
         }
     }
 
@@ -163,11 +253,24 @@ pub struct GameScene<'a> {
     sprites: Sprites,
     context: &'a mut SceneContext,
     jobs: collections::VecDeque<Job>,
+    unsimulated_time: f64,
+    block_fade_delay: Option<f64>,
+    name_entry: Option<String>,
+    player_input: PlayerInput,
+    rebinding: Option<Rebinding>,
+    title: String,
+    replay: Option<String>,
 }
 
 impl<'a> GameScene<'a> {
-    pub fn new(context: &'a mut SceneContext) -> Self {
-        let mut game = Game::new();
+    pub fn new(context: &'a mut SceneContext, game: Game) -> Self {
+        let mut game = game
+            .with_timings(Timings {
+                entry: ENTRY_DELAY,
+                line_clear: LINE_CLEAR_DELAY,
+                ..Timings::default()
+            })
+            .with_delayed_first_piece();
         let sprites = Sprites::new(game.stage_size(), context);
         let initial_events = game.initial_events();
         let mut result = Self {
@@ -175,15 +278,50 @@ impl<'a> GameScene<'a> {
             sprites,
             context,
             jobs: Default::default(),
+            unsimulated_time: 0.0,
+            block_fade_delay: None,
+            name_entry: None,
+            player_input: PlayerInput::default(),
+            rebinding: None,
+            title: WINDOW_TITLE.to_owned(),
+            replay: None,
         };
         result.apply_game_events(initial_events);
         result
     }
 
+    pub fn with_block_fade_delay(self, block_fade_delay: f64) -> Self {
+        Self {
+            block_fade_delay: Some(block_fade_delay),
+            ..self
+        }
+    }
+
+    pub fn with_controls(self, controls: Controls) -> Self {
+        Self {
+            player_input: PlayerInput::new(controls),
+            ..self
+        }
+    }
+
+    pub fn with_replay(self, replay: String) -> Self {
+        Self {
+            replay: Some(replay),
+            ..self
+        }
+    }
+
     fn stage_size(&self) -> BlockGridSize {
         self.game.stage_size()
     }
 
+    fn flash_blocks(&mut self) {
+        if self.block_fade_delay.is_some() {
+            self.sprites
+                .show_all_blocks(BLOCK_FLASH_DURATION + BLOCK_FADE_DURATION);
+        }
+    }
+
     fn is_ready(&self) -> bool {
         !self.sprites.is_running()
     }
@@ -254,6 +392,17 @@ impl<'a> GameScene<'a> {
         }
     }
 
+    fn update_hold_piece(&mut self, piece: Option<Piece>) {
+        let parent = self.sprites.hold_piece_sprite();
+        parent.remove_all_children();
+        put_background_tile_sprites(parent, BlockGridSize::new(4, 4), self.context);
+        if let Some(piece) = piece {
+            let mut sprite = self.context.empty_sprite();
+            Self::put_piece_sprites(self.context, &mut sprite, &piece, false);
+            parent.add_child(sprite);
+        }
+    }
+
     fn put_blocks(&mut self, results: Vec<PutResult>) {
         for result in &results {
             let id = sprite::Sprite::from_texture(
@@ -264,9 +413,18 @@ impl<'a> GameScene<'a> {
             .moved_to(result.index.to_pixel_space(self.stage_size()))
             .add_to(self.sprites.stage_sprite());
             self.sprites.set_block_sprite_id(result.index, id);
+            if let Some(block_fade_delay) = self.block_fade_delay {
+                self.sprites
+                    .set_block_visible_time(result.index, block_fade_delay + BLOCK_FADE_DURATION);
+            }
         }
     }
 
+    fn restore_blocks(&mut self, results: Vec<PutResult>) {
+        self.sprites.remove_all_block_sprites();
+        self.put_blocks(results);
+    }
+
     fn add_removing_action(&mut self, remove_result: &RemoveResult) {
         use ai_behavior::{Action, Sequence};
         use sprite::{Ease, EaseFunction, ScaleTo};
@@ -314,6 +472,119 @@ impl<'a> GameScene<'a> {
         }
     }
 
+    fn flag_finesse_fault(&mut self) {
+        use ai_behavior::{Action, Sequence};
+        use sprite::MoveBy;
+        self.sprites.scene.run(
+            self.sprites.stage_sprite_id,
+            &Sequence(vec![
+                Action(MoveBy(0.05, TILE_SIZE / 2.0, 0.0)),
+                Action(MoveBy(0.1, -TILE_SIZE, 0.0)),
+                Action(MoveBy(0.05, TILE_SIZE / 2.0, 0.0)),
+            ]),
+        );
+    }
+
+    fn show_text(&mut self, lines: &[String]) {
+        let mut sprite = Sprite::from_texture(self.context.assets.text_texture(lines));
+        sprite.set_anchor(0.0, 0.0);
+        let parent = self.sprites.text_sprite();
+        parent.remove_all_children();
+        parent.add_child(sprite);
+    }
+
+    fn show_high_scores(&mut self, table: &HighScoreTable) {
+        let mode = self.game.mode();
+        let mut lines = vec!["high scores".to_owned(), mode.name().to_owned()];
+        lines.extend(table.entries(mode).iter().enumerate().map(|(rank, entry)| {
+            let record = if mode.raises_floor() {
+                format!("{:.1}s", entry.frames as f64 / FRAME_RATE as f64)
+            } else {
+                entry.score.to_string()
+            };
+            let name = entry.name.chars().take(MAX_NAME_LENGTH).collect::<String>();
+            format!("{:>2} {:<8} {:>7}", rank + 1, name, record)
+        }));
+        self.show_text(&lines);
+    }
+
+    fn show_name_entry(&mut self) {
+        if let Some(name) = &self.name_entry {
+            let lines = ["new high score!".to_owned(), format!("name: {}_", name)];
+            self.show_text(&lines);
+        }
+    }
+
+    fn load_high_scores() -> HighScoreTable {
+        high_scores::default_path().map_or_else(Default::default, |path| {
+            HighScoreTable::load(&path).unwrap_or_else(|error| {
+                eprintln!("can not load the high scores: {}", error);
+                Default::default()
+            })
+        })
+    }
+
+    fn finish_game(&mut self) {
+        eprintln!("{}", self.game.stats());
+        if self.game.mode() == GameMode::Practice {
+            return;
+        }
+        let table = Self::load_high_scores();
+        let high_score = HighScore::from_game(&self.game, String::new());
+        if table.rank(self.game.mode(), &high_score).is_some() {
+            self.name_entry = Some(String::new());
+            self.show_name_entry();
+        } else {
+            self.show_high_scores(&table);
+        }
+    }
+
+    fn record_high_score(&mut self, name: String) {
+        let mut table = Self::load_high_scores();
+        let high_score = HighScore::from_game(&self.game, name).with_replay(self.replay.clone());
+        table.insert(self.game.mode(), high_score);
+        if let Some(path) = high_scores::default_path() {
+            if let Err(error) = table.save(&path) {
+                eprintln!("can not save the high scores: {}", error);
+            }
+        }
+        self.show_high_scores(&table);
+    }
+
+    fn input_name(&mut self, input: Input) {
+        let name = match self.name_entry.as_mut() {
+            Some(name) => name,
+            None => return,
+        };
+        match input {
+            Input::Text(text) => {
+                let length = name.chars().count();
+                name.extend(
+                    text.chars()
+                        .filter(|c| !c.is_control())
+                        .take(MAX_NAME_LENGTH.saturating_sub(length)),
+                );
+                self.show_name_entry();
+            }
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button: Button::Keyboard(key),
+                ..
+            }) => match key {
+                Key::Backspace => {
+                    name.pop();
+                    self.show_name_entry();
+                }
+                Key::Return => {
+                    let name = self.name_entry.take().unwrap();
+                    self.record_high_score(name);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
     fn apply_game_event(&mut self, event: GameEvent) {
         use GameEvent::*;
         match event {
@@ -336,15 +607,31 @@ impl<'a> GameScene<'a> {
             UpdateNextPieces(pieces) => {
                 self.update_next_pieces(pieces);
             }
+            UpdateHoldPiece(piece) => {
+                self.update_hold_piece(piece);
+            }
             PutBlocks(results) => {
                 self.put_blocks(results);
             }
+            RestoreBlocks(results) => {
+                self.restore_blocks(results);
+            }
             RemoveBlocks(results) => {
+                self.flash_blocks();
                 self.remove_blocks(results);
             }
             MoveBlocks(results) => {
                 self.move_blocks(results);
             }
+            ChangePhase(_) => {}
+            Chain(_) => {}
+            FinesseFault { .. } => {
+                self.flag_finesse_fault();
+            }
+            GameOver => {
+                self.flash_blocks();
+                self.finish_game();
+            }
         }
     }
 
@@ -376,34 +663,145 @@ impl<'a> GameScene<'a> {
 
     fn update(&mut self, delta: f64) {
         self.execute_jobs();
-        if self.is_ready() {
-            let events = self.game.update(delta);
-            self.apply_game_events(events);
+        if self.block_fade_delay.is_some() {
+            self.sprites.update_block_opacities(delta);
+        }
+        if self.player_input.is_paused() || self.rebinding.is_some() {
+            return;
+        }
+        let frame_duration = 1.0 / FRAME_RATE as f64;
+        self.unsimulated_time =
+            (self.unsimulated_time + delta).min(MAX_FRAMES_PER_UPDATE as f64 * frame_duration);
+        let mut events = vec![];
+        while self.unsimulated_time >= frame_duration {
+            self.unsimulated_time -= frame_duration;
+            events.append(&mut self.player_input.tick(&mut self.game));
+        }
+        self.apply_game_events(events);
+    }
+
+    fn start_rebinding(&mut self) {
+        self.player_input.release_all(&mut self.game);
+        eprintln!("press the keys for each action (enter: next, backspace: cancel)");
+        self.rebinding = Some(Rebinding {
+            key_map: self.player_input.controls().keys.clone(),
+            action_index: 0,
+            keys: vec![],
+        });
+    }
+
+    fn finish_rebinding(&mut self, key_map: KeyMap) {
+        self.player_input.controls_mut().keys = key_map;
+        if let Some(path) = controls::default_path() {
+            if let Err(error) = self.player_input.controls().save(&path) {
+                eprintln!("can not save the controls: {}", error);
+            }
+        }
+    }
+
+    fn input_rebinding(&mut self, input: Input) {
+        let key = match input {
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button: Button::Keyboard(key),
+                ..
+            }) => key,
+            _ => return,
+        };
+        let rebinding = match self.rebinding.as_mut() {
+            Some(rebinding) => rebinding,
+            None => return,
+        };
+        match key {
+            Key::Return => {
+                if !rebinding.keys.is_empty() {
+                    let action = rebinding.action();
+                    let keys = mem::take(&mut rebinding.keys);
+                    rebinding.key_map.bind(action, keys);
+                }
+                rebinding.action_index += 1;
+                if rebinding.action_index == Action::ALL.len() {
+                    let rebinding = self.rebinding.take().unwrap();
+                    self.finish_rebinding(rebinding.key_map);
+                }
+            }
+            Key::Backspace => {
+                self.rebinding = None;
+            }
+            Key::F1 => {}
+            key => {
+                let name = key_name(key);
+                if !rebinding.keys.contains(&name) {
+                    rebinding.keys.push(name);
+                }
+            }
         }
     }
 
     fn input(&mut self, input: Input) {
-        if !self.is_ready() {
+        if self.name_entry.is_some() {
+            self.input_name(input);
             return;
         }
-        match input {
+        if self.rebinding.is_some() {
+            self.input_rebinding(input);
+            return;
+        }
+        let control_input = match input {
             Input::Button(ButtonArgs {
                 state: ButtonState::Press,
+                button: Button::Keyboard(Key::F1),
+                ..
+            }) if !self.game.is_game_over() => {
+                self.start_rebinding();
+                return;
+            }
+            Input::Button(ButtonArgs {
+                state,
                 button: Button::Keyboard(key),
                 ..
-            }) => {
-                let events = match key {
-                    Key::Left => self.game.move_piece_left(),
-                    Key::Right => self.game.move_piece_right(),
-                    Key::Down => self.game.drop_piece_soft(),
-                    Key::Up => self.game.drop_piece_hard(),
-                    Key::Z => self.game.rotate_piece_left(),
-                    Key::X => self.game.rotate_piece_right(),
-                    _ => return,
-                };
-                self.apply_game_events(events);
+            }) => ControlInput::Key {
+                name: key_name(key),
+                is_pressed: state == ButtonState::Press,
+            },
+            Input::Button(ButtonArgs {
+                state,
+                button: Button::Controller(ControllerButton { id, button }),
+                ..
+            }) => ControlInput::ControllerButton {
+                id,
+                button,
+                is_pressed: state == ButtonState::Press,
+            },
+            Input::Move(Motion::ControllerAxis(ControllerAxisArgs { id, axis, position })) => {
+                ControlInput::ControllerAxis { id, axis, position }
             }
-            _ => {}
+            _ => return,
+        };
+        self.apply_control_input(control_input);
+    }
+
+    pub fn apply_control_input(&mut self, input: ControlInput) {
+        if self.name_entry.is_some() || self.rebinding.is_some() {
+            return;
+        }
+        let events = self.player_input.input(&mut self.game, input);
+        self.apply_game_events(events);
+    }
+
+    fn window_title(&self) -> String {
+        if let Some(rebinding) = &self.rebinding {
+            let action = rebinding.action();
+            format!(
+                "{} [{}]: {}_",
+                action.name(),
+                rebinding.key_map.keys(action).join(" "),
+                rebinding.keys.join(" ")
+            )
+        } else if self.player_input.is_paused() {
+            format!("{} paused", WINDOW_TITLE)
+        } else {
+            WINDOW_TITLE.to_owned()
         }
     }
 
@@ -421,6 +819,11 @@ impl<'a> GameScene<'a> {
             }
             Event::Input(input, _) => {
                 self.input(input);
+                let title = self.window_title();
+                if title != self.title {
+                    window.set_title(title.clone());
+                    self.title = title;
+                }
             }
             _ => {}
         }
@@ -0,0 +1,242 @@
+use crate::directories;
+use crate::game::{Game, GameMode};
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::collections;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+use std::time;
+
+const TABLE_SIZE: usize = 10;
+const FILE_NAME: &str = "high-scores.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    pub lines: usize,
+    pub frames: u64,
+    pub date: u64,
+    #[serde(default)]
+    pub replay: Option<String>,
+}
+
+impl HighScore {
+    pub fn from_game(game: &Game, name: String) -> Self {
+        Self {
+            name,
+            score: game.score(),
+            lines: game.cleared_lines(),
+            frames: game.stats().frames,
+            date: time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            replay: None,
+        }
+    }
+
+    pub fn with_replay(self, replay: Option<String>) -> Self {
+        Self { replay, ..self }
+    }
+}
+
+fn compare(mode: GameMode, a: &HighScore, b: &HighScore) -> cmp::Ordering {
+    if mode.raises_floor() {
+        b.frames.cmp(&a.frames).then(b.score.cmp(&a.score))
+    } else {
+        b.score.cmp(&a.score).then(a.frames.cmp(&b.frames))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    modes: collections::BTreeMap<String, Vec<HighScore>>,
+}
+
+impl HighScoreTable {
+    pub fn load(path: &path::Path) -> io::Result<Self> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+        match serde_json::from_str(&source) {
+            Ok(table) => Ok(table),
+            Err(_) => {
+                fs::rename(path, path.with_extension("json.corrupt"))?;
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn save(&self, path: &path::Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&temporary_path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.write_all(b"\n")?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, path)
+    }
+
+    pub fn entries(&self, mode: GameMode) -> &[HighScore] {
+        self.modes.get(mode.name()).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn rank(&self, mode: GameMode, high_score: &HighScore) -> Option<usize> {
+        let entries = self.entries(mode);
+        let rank = entries
+            .iter()
+            .position(|entry| compare(mode, high_score, entry) == cmp::Ordering::Less)
+            .unwrap_or(entries.len());
+        if rank < TABLE_SIZE {
+            Some(rank)
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, mode: GameMode, high_score: HighScore) -> Option<usize> {
+        let rank = self.rank(mode, &high_score)?;
+        let entries = self.modes.entry(mode.name().to_owned()).or_default();
+        entries.insert(rank, high_score);
+        entries.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+
+    pub fn display(&self, mode: GameMode) -> HighScoreTableDisplay<'_> {
+        HighScoreTableDisplay { table: self, mode }
+    }
+}
+
+pub struct HighScoreTableDisplay<'a> {
+    table: &'a HighScoreTable,
+    mode: GameMode,
+}
+
+impl fmt::Display for HighScoreTableDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "high scores ({})", self.mode.name())?;
+        for (rank, entry) in self.table.entries(self.mode).iter().enumerate() {
+            write!(
+                f,
+                "\n{:>2}. {:<16} {:>8} {:>4} lines {:>7.1}s",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.lines,
+                entry.frames as f64 / crate::game::FRAME_RATE as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn default_path() -> Option<path::PathBuf> {
+    directories::data_directory().map(|directory| directory.join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn high_score(name: &str, score: u64, frames: u64) -> HighScore {
+        HighScore {
+            name: name.to_owned(),
+            score,
+            lines: 0,
+            frames,
+            date: 0,
+            replay: None,
+        }
+    }
+
+    fn names(table: &HighScoreTable, mode: GameMode) -> Vec<&str> {
+        let entries = table.entries(mode).iter();
+        entries.map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn temporary_path(test_name: &str) -> path::PathBuf {
+        let directory =
+            env::temp_dir().join(format!("high-scores-{}-{}", process::id(), test_name));
+        let _ = fs::remove_dir_all(&directory);
+        directory.join(FILE_NAME)
+    }
+
+    #[test]
+    fn ranks_by_score_and_then_by_time() {
+        let mut table = HighScoreTable::default();
+        table.insert(GameMode::Normal, high_score("a", 100, 600));
+        table.insert(GameMode::Normal, high_score("b", 300, 900));
+        table.insert(GameMode::Normal, high_score("c", 300, 600));
+        assert_eq!(names(&table, GameMode::Normal), vec!["c", "b", "a"]);
+        assert!(table.entries(GameMode::Practice).is_empty());
+    }
+
+    #[test]
+    fn ranks_survival_by_time() {
+        let mut table = HighScoreTable::default();
+        table.insert(GameMode::Survival, high_score("a", 1000, 600));
+        table.insert(GameMode::Survival, high_score("b", 10, 1200));
+        assert_eq!(names(&table, GameMode::Survival), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn keeps_only_the_best_entries() {
+        let mut table = HighScoreTable::default();
+        for score in 1..=TABLE_SIZE as u64 + 2 {
+            table.insert(GameMode::Normal, high_score("", score * 10, 0));
+        }
+        let scores = table
+            .entries(GameMode::Normal)
+            .iter()
+            .map(|entry| entry.score);
+        let expected = (3..=TABLE_SIZE as u64 + 2).rev().map(|score| score * 10);
+        assert_eq!(scores.collect::<Vec<_>>(), expected.collect::<Vec<_>>());
+        assert_eq!(table.rank(GameMode::Normal, &high_score("", 25, 0)), None);
+        assert_eq!(table.insert(GameMode::Normal, high_score("", 25, 0)), None);
+        let rank = table.rank(GameMode::Normal, &high_score("", 35, 0));
+        assert_eq!(rank, Some(TABLE_SIZE - 1));
+        assert_eq!(table.entries(GameMode::Normal).len(), TABLE_SIZE);
+    }
+
+    #[test]
+    fn loads_an_empty_table_without_a_file() {
+        let path = temporary_path("missing");
+        assert_eq!(
+            HighScoreTable::load(&path).unwrap(),
+            HighScoreTable::default()
+        );
+    }
+
+    #[test]
+    fn saves_and_loads_tables() {
+        let path = temporary_path("save");
+        let mut table = HighScoreTable::default();
+        table.insert(GameMode::Normal, high_score("a", 100, 600));
+        table.save(&path).unwrap();
+        assert_eq!(HighScoreTable::load(&path).unwrap(), table);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn moves_corrupt_files_aside() {
+        let path = temporary_path("corrupt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{\"modes\": [").unwrap();
+        assert_eq!(
+            HighScoreTable::load(&path).unwrap(),
+            HighScoreTable::default()
+        );
+        assert!(!path.exists());
+        let corrupt_path = path.with_extension("json.corrupt");
+        assert_eq!(fs::read_to_string(&corrupt_path).unwrap(), "{\"modes\": [");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
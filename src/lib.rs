@@ -0,0 +1,6 @@
+pub mod bot;
+pub mod controls;
+pub mod directories;
+pub mod event_log;
+pub mod game;
+pub mod high_scores;
@@ -1,22 +1,93 @@
 mod assets;
-mod game;
 mod game_scene;
+mod pixel_font;
 mod scene_context;
 mod sprite_ext;
 
-use game_scene::GameScene;
+use game_scene::{GameScene, DEFAULT_BLOCK_FADE_DELAY, WINDOW_TITLE};
 use piston_window::*;
+use rust_awesome_puzzle_game_example::event_log::EventLog;
+use rust_awesome_puzzle_game_example::{controls, game, high_scores};
 use scene_context::SceneContext;
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str =
+    "usage: rust-awesome-puzzle-game-example [mode] [piece set] [block fade delay in seconds]";
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
 
 fn main() {
-    let mut window: PistonWindow = WindowSettings::new("( o_o)", (320, 320))
+    let mut args = env::args().skip(1);
+    let mode = args.next().map_or(Default::default(), |name| {
+        game::GameMode::from_name(&name).unwrap_or_else(|| {
+            let names = game::GameMode::ALL.iter().map(|mode| mode.name());
+            exit_with_usage(&format!(
+                "unknown game mode: {}\nthe modes are: {}",
+                name,
+                names.collect::<Vec<_>>().join(", ")
+            ))
+        })
+    });
+    let piece_set = args.next().map_or_else(game::PieceSet::standard, |name| {
+        game::piece_set::builtin_or_load(&name, game::Game::default_stage_size())
+            .unwrap_or_else(|error| exit_with_usage(&error.to_string()))
+    });
+    let block_fade_delay = args.next().map_or(DEFAULT_BLOCK_FADE_DELAY, |delay| {
+        delay
+            .parse()
+            .ok()
+            .filter(|&delay: &f64| delay >= 0.0)
+            .unwrap_or_else(|| {
+                exit_with_usage(&format!(
+                    "the block fade delay must be a non-negative number of seconds: {}",
+                    delay
+                ))
+            })
+    });
+    let mut game = game::Game::with_piece_set(mode, piece_set);
+    if let Ok(match_size) = env::var("MATCH_SIZE") {
+        game = game.with_match_size(match_size.parse().unwrap_or_else(|_| {
+            exit_with_usage(&format!(
+                "the match size must be a positive number: {}",
+                match_size
+            ))
+        }));
+    }
+    let event_log = env::var("EVENT_LOG").ok();
+    if let Some(name) = &event_log {
+        EventLog::open(name)
+            .unwrap_or_else(|error| panic!("can not open the event log: {}", error))
+            .subscribe(&mut game);
+    }
+    let mut window: PistonWindow = WindowSettings::new(WINDOW_TITLE, (320, 320))
         .resizable(false)
         .automatic_close(true)
         .build()
         .expect("failed to start the game");
     window.set_max_fps(15);
     let mut scene_context = SceneContext::new(&mut window);
-    let mut game_scene = GameScene::new(&mut scene_context);
+    let controls = controls::default_path().map_or_else(Default::default, |path| {
+        controls::Controls::load(&path).unwrap_or_else(|error| {
+            eprintln!("can not load the controls: {}", error);
+            Default::default()
+        })
+    });
+    let mut game_scene = GameScene::new(&mut scene_context, game).with_controls(controls);
+    if let Some(path) = event_log
+        .filter(|name| name != "-")
+        .and_then(|name| fs::canonicalize(name).ok())
+    {
+        game_scene = game_scene.with_replay(path.display().to_string());
+    }
+    if mode == game::GameMode::Invisible {
+        game_scene = game_scene.with_block_fade_delay(block_fade_delay);
+    }
     while let Some(event) = window.next() {
         game_scene.apply_window_event(event, &mut window);
     }
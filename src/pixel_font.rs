@@ -0,0 +1,98 @@
+const GLYPH_WIDTH: u32 = 3;
+
+const GLYPH_HEIGHT: u32 = 5;
+
+const LETTER_SPACING: u32 = 1;
+
+const LINE_SPACING: u32 = 2;
+
+const PADDING: u32 = 2;
+
+const TEXT_COLOR: image::Rgba<u8> = image::Rgba {
+    data: [255, 255, 255, 255],
+};
+
+const BACKGROUND_COLOR: image::Rgba<u8> = image::Rgba {
+    data: [0, 0, 0, 192],
+};
+
+type Glyph = [u8; GLYPH_HEIGHT as usize];
+
+fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+pub fn render(lines: &[String]) -> image::RgbaImage {
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count() as u32)
+        .max()
+        .unwrap_or(0);
+    let rows = lines.len() as u32;
+    let width =
+        PADDING * 2 + (columns * (GLYPH_WIDTH + LETTER_SPACING)).saturating_sub(LETTER_SPACING);
+    let height = PADDING * 2 + (rows * (GLYPH_HEIGHT + LINE_SPACING)).saturating_sub(LINE_SPACING);
+    let mut image = image::RgbaImage::from_pixel(width, height, BACKGROUND_COLOR);
+    for (row, line) in lines.iter().enumerate() {
+        let top = PADDING + row as u32 * (GLYPH_HEIGHT + LINE_SPACING);
+        for (column, c) in line.chars().enumerate() {
+            let left = PADDING + column as u32 * (GLYPH_WIDTH + LETTER_SPACING);
+            for (y, bits) in glyph(c).iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                        image.put_pixel(left + x, top + y as u32, TEXT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+    image
+}